@@ -0,0 +1,169 @@
+//! Defines the environment variables to use.
+
+#![cfg(feature = "env")]
+
+use crate::static_lazy_lock;
+
+mod secret;
+mod source;
+
+pub mod parse;
+pub mod reload;
+
+pub use secret::*;
+pub use source::*;
+
+/// Parses an environment variable from [`String`] to something else, wrapping any error in [`anyhow::Error`] that names the variable.
+///
+/// # Examples
+///
+/// ```rust
+/// # use api_framework::parse_env;
+/// # use std::time::Duration;
+/// // with a custom parser returning an `anyhow::Result`...
+/// let port = parse_env!("PORT" => |s| Ok(s.len()));
+/// // ...or any other `Result`
+/// let port = parse_env!("PORT" => |s| s.parse::<u16>(); anyhow);
+///
+/// // with a built-in parser, like `30s` or `1h30m`...
+/// let timeout = parse_env!("TIMEOUT" => duration);
+/// // ...like `512MiB` or `1.5 GB`...
+/// let max_size = parse_env!("MAX_SIZE" => bytes);
+/// // ...like `true`, `1`, `yes` or `on`...
+/// let verbose = parse_env!("VERBOSE" => bool);
+/// // ...like `a,b,c` or `a;b;c`...
+/// let branches = parse_env!("BRANCHES" => list<String>);
+/// let ports = parse_env!("PORTS" => list<u16>(';'));
+/// // ...or any type implementing `FromStr`, like enums
+/// let level = parse_env!("LEVEL" => tracing::Level);
+///
+/// // falls back to a default value if the variable is not set or fails to parse
+/// let timeout: Duration = parse_env!("TIMEOUT" => duration; default Duration::from_secs(30));
+/// ```
+#[macro_export]
+macro_rules! parse_env {
+    (@default $key:expr, |$var:ident| $result:expr, $default:expr) => {{
+        let $var = &$key;
+        $crate::env::__priv_macro_use::or_default($var, $result, || $default)
+    }};
+    ($key:expr => |$var:ident| $expr:expr; anyhow; default $default:expr) => {
+        $crate::env::__priv_macro_use::parse_env!(@default $key, |key| $crate::env::__priv_macro_use::parse_env!(key => |$var| $expr; anyhow), $default)
+    };
+    ($key:expr => |$var:ident| $expr:expr; default $default:expr) => {
+        $crate::env::__priv_macro_use::parse_env!(@default $key, |key| $crate::env::__priv_macro_use::parse_env!(key => |$var| $expr), $default)
+    };
+    ($key:expr => |$var:ident| $expr:expr) => {
+        $crate::env::__priv_macro_use::parse($key, |$var| $expr)
+    };
+    ($key:expr => |$var:ident| $expr:expr; anyhow) => {
+        $crate::env::__priv_macro_use::parse_env!($key => |$var| $expr.map_err(|e| $crate::env::__priv_macro_use::anyhow::anyhow!(e)))
+    };
+    ($key:expr => $($kind:ident)::+ $(<$ty:ty>)? $(($separator:literal))?; default $default:expr) => {
+        $crate::env::__priv_macro_use::parse_env!(@default $key, |key| $crate::env::__priv_macro_use::parse_env!(key => $($kind)::+ $(<$ty>)? $(($separator))?), $default)
+    };
+    ($key:expr => duration) => {
+        $crate::env::__priv_macro_use::parse_env!($key => |s| $crate::env::parse::duration(&s))
+    };
+    ($key:expr => bytes) => {
+        $crate::env::__priv_macro_use::parse_env!($key => |s| $crate::env::parse::byte_size(&s))
+    };
+    ($key:expr => bool) => {
+        $crate::env::__priv_macro_use::parse_env!($key => |s| $crate::env::parse::boolean(&s))
+    };
+    ($key:expr => list<$ty:ty>) => {
+        $crate::env::__priv_macro_use::parse_env!($key => list<$ty>(','))
+    };
+    ($key:expr => list<$ty:ty>($separator:literal)) => {
+        $crate::env::__priv_macro_use::parse_env!($key => |s| $crate::env::parse::list::<$ty>(&s, $separator))
+    };
+    ($key:expr => $ty:ty) => {
+        $crate::env::__priv_macro_use::parse_env!($key => |s| $crate::env::parse::from_str::<$ty>(&s))
+    };
+}
+
+#[cfg(feature = "env_github_token")]
+static_lazy_lock! {
+    /// The GitHub token. Can also be loaded from the file at `GITHUB_TOKEN_FILE`.
+    ///
+    /// Prefer `GitHubAuth::from_env`, which also authenticates as a GitHub App or with a token pool, and returns an error instead of panicking.
+    ///
+    /// # Panics
+    ///
+    /// Panics on first access if neither is set, or the file cannot be read.
+    pub GITHUB_TOKEN: Secret<String> = load_github_token().unwrap_or_else(|e| panic!("{e:#}"));
+}
+
+#[cfg(feature = "env_github_token")]
+static_lazy_lock! {
+    /// Tracks `GITHUB_TOKEN` on reload, which only takes effect after a restart since clients capture it once built.
+    pub(crate) RELOADABLE_GITHUB_TOKEN: reload::Reloadable<Option<Secret<String>>> =
+        reload::Reloadable::new("GITHUB_TOKEN", || Ok(Secret::from_env("GITHUB_TOKEN").ok()))
+            .requires_restart();
+}
+
+/// Loads `GITHUB_TOKEN`, first tracking it in [`RELOADABLE_GITHUB_TOKEN`] so that the value compared on reload is the one loaded here.
+#[cfg(feature = "env_github_token")]
+pub(crate) fn load_github_token() -> anyhow::Result<Secret<String>> {
+    std::sync::LazyLock::force(&RELOADABLE_GITHUB_TOKEN);
+    Secret::from_env("GITHUB_TOKEN")
+}
+
+#[cfg(feature = "env_max_retries")]
+static_lazy_lock! {
+    /// The maximum retry limit for transactions, as loaded on first access.
+    #[deprecated(note = "does not follow reloads, use `max_retries` instead")]
+    pub MAX_RETRIES: u8 = max_retries();
+}
+
+#[cfg(feature = "env_max_retries")]
+static_lazy_lock! {
+    /// The maximum retry limit for transactions, reloadable at runtime. Falls back to 5 if missing or invalid.
+    pub RELOADABLE_MAX_RETRIES: reload::Reloadable<u8> = reload::Reloadable::new("MAX_RETRIES", || {
+        Ok(parse_env!("MAX_RETRIES" => u8; default 5))
+    });
+}
+
+/// Gets the current maximum retry limit for transactions.
+///
+/// See: [`RELOADABLE_MAX_RETRIES`]
+#[cfg(feature = "env_max_retries")]
+pub fn max_retries() -> u8 {
+    *RELOADABLE_MAX_RETRIES.get()
+}
+
+#[doc(hidden)]
+pub mod __priv_macro_use {
+    pub use crate::parse_env;
+    pub use anyhow;
+    pub use std::env;
+
+    use anyhow::Context as _;
+    use tracing::warn;
+
+    pub fn parse<K, T, F>(key: K, f: F) -> anyhow::Result<T>
+    where
+        K: AsRef<str>,
+        F: FnOnce(String) -> anyhow::Result<T>,
+    {
+        let key = key.as_ref();
+        let value = crate::env::var(key)
+            .with_context(|| format!("failed to read environment variable {key}"))?;
+        f(value).with_context(|| format!("failed to parse environment variable {key}"))
+    }
+
+    pub fn or_default<K, T, F>(key: K, result: anyhow::Result<T>, default: F) -> T
+    where
+        K: AsRef<str>,
+        F: FnOnce() -> T,
+    {
+        result.unwrap_or_else(|e| {
+            if e.downcast_ref::<env::VarError>() != Some(&env::VarError::NotPresent) {
+                warn!(
+                    "{e:#}, falling back to the default value of {}",
+                    key.as_ref()
+                );
+            }
+            default()
+        })
+    }
+}
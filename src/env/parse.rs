@@ -0,0 +1,210 @@
+//! Built-in parsers for environment variables, used by [`parse_env!`](crate::parse_env).
+
+use std::{env::VarError, fmt::Display, str::FromStr, time::Duration};
+
+use anyhow::{anyhow, bail};
+
+/// Parses a human readable duration, like `30s`, `1h30m` or `250ms`. A bare number is treated as seconds.
+///
+/// Supported units are `ns`, `us`, `ms`, `s`, `m`, `h` and `d`.
+///
+/// # Errors
+///
+/// Returns an error if the input is empty, contains an invalid number or an unknown unit, or overflows.
+pub fn duration(s: &str) -> anyhow::Result<Duration> {
+    let s = s.trim();
+    if s.is_empty() {
+        bail!("empty duration");
+    }
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            bail!("invalid duration {s:?}: expected a number");
+        }
+        let value = rest[..digits].parse::<u64>()?;
+        rest = &rest[digits..];
+
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let part = match rest[..unit].trim() {
+            "ns" => Some(Duration::from_nanos(value)),
+            "us" | "µs" => Some(Duration::from_micros(value)),
+            "ms" => Some(Duration::from_millis(value)),
+            "s" => Some(Duration::from_secs(value)),
+            "m" => value.checked_mul(60).map(Duration::from_secs),
+            "h" => value.checked_mul(60 * 60).map(Duration::from_secs),
+            "d" => value.checked_mul(60 * 60 * 24).map(Duration::from_secs),
+            "" => bail!("invalid duration {s:?}: missing unit after {value}"),
+            unit => bail!("invalid duration {s:?}: unknown unit {unit:?}"),
+        };
+        total = part
+            .and_then(|part| total.checked_add(part))
+            .ok_or_else(|| anyhow!("invalid duration {s:?}: too large"))?;
+        rest = &rest[unit..];
+    }
+
+    Ok(total)
+}
+
+/// Parses a human readable byte size, like `512MiB`, `1.5 GB` or `1024`. A bare number is treated as bytes.
+///
+/// Decimal units (`KB`, `MB`, `GB`, `TB`, or `K`, `M`, `G`, `T`) are powers of 1000, while binary units (`KiB`, `MiB`, `GiB`, `TiB`) are powers of 1024. Units are case-insensitive.
+///
+/// # Errors
+///
+/// Returns an error if the input contains an invalid number or an unknown unit.
+pub fn byte_size(s: &str) -> anyhow::Result<u64> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = (&s[..split], s[split..].trim());
+
+    let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "m" | "mb" => 1000_u64.pow(2),
+        "g" | "gb" => 1000_u64.pow(3),
+        "t" | "tb" => 1000_u64.pow(4),
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => bail!("invalid byte size {s:?}: unknown unit {unit:?}"),
+    };
+
+    if let Ok(value) = number.parse::<u64>() {
+        return value
+            .checked_mul(multiplier)
+            .ok_or_else(|| anyhow!("invalid byte size {s:?}: too large"));
+    }
+
+    let value = match number.parse::<f64>() {
+        Ok(value) if value.is_finite() && value >= 0.0 => value,
+        _ => bail!("invalid byte size {s:?}: expected a number"),
+    };
+    // `u64::MAX as f64` rounds up to 2^64, which is already out of range
+    let bytes = (value * multiplier as f64).round();
+    if !bytes.is_finite() || bytes >= u64::MAX as f64 {
+        bail!("invalid byte size {s:?}: too large");
+    }
+    Ok(bytes as u64)
+}
+
+/// Parses a boolean. Accepts `true`, `1`, `yes`, `y`, `on` and `false`, `0`, `no`, `n`, `off`, case-insensitively.
+///
+/// # Errors
+///
+/// Returns an error if the input is none of the above.
+pub fn boolean(s: &str) -> anyhow::Result<bool> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "y" | "on" => Ok(true),
+        "false" | "0" | "no" | "n" | "off" => Ok(false),
+        _ => bail!("invalid boolean {s:?}: expected one of true, false, 1, 0, yes, no, on or off"),
+    }
+}
+
+/// Parses a list delimited by `separator`. Items are trimmed and empty items are skipped.
+///
+/// # Errors
+///
+/// Returns an error naming the first item that fails to parse.
+pub fn list<T>(s: &str, separator: char) -> anyhow::Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    s.split(separator)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .enumerate()
+        .map(|(index, item)| {
+            from_str(item).map_err(|e| e.context(format!("invalid item at index {index}")))
+        })
+        .collect()
+}
+
+/// Parses a value through its [`FromStr`] implementation. Useful for enums.
+///
+/// # Errors
+///
+/// Returns an error containing the offending input if parsing fails.
+pub fn from_str<T>(s: &str) -> anyhow::Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    s.trim()
+        .parse::<T>()
+        .map_err(|e| anyhow!("invalid value {s:?}: {e}"))
+}
+
+/// Converts a missing environment variable into [`None`], keeping other errors as-is.
+///
+/// # Errors
+///
+/// Returns the original error if it is not caused by a missing variable.
+pub fn optional<T>(result: anyhow::Result<T>) -> anyhow::Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.downcast_ref::<VarError>() == Some(&VarError::NotPresent) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(duration("30").unwrap(), Duration::from_secs(30));
+        assert_eq!(duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(duration("1d 2h").unwrap(), Duration::from_secs(93600));
+        assert!(duration("").is_err());
+        assert!(duration("5 fortnights").is_err());
+        assert!(duration("m").is_err());
+        assert!(duration("18446744073709551615d").is_err());
+        assert!(duration("18446744073709551615s 1s").is_err());
+    }
+
+    #[test]
+    fn borrows_owned_keys_for_defaults() {
+        let key = String::from("API_FRAMEWORK_TEST_UNSET_TIMEOUT");
+        let timeout = crate::parse_env!(key => duration; default Duration::from_secs(30));
+        assert_eq!(timeout, Duration::from_secs(30));
+        assert!(key.ends_with("TIMEOUT"));
+    }
+
+    #[test]
+    fn parses_byte_sizes() {
+        assert_eq!(byte_size("1024").unwrap(), 1024);
+        assert_eq!(byte_size("512MiB").unwrap(), 512 << 20);
+        assert_eq!(byte_size("1.5 GB").unwrap(), 1_500_000_000);
+        assert_eq!(byte_size("10k").unwrap(), 10_000);
+        assert!(byte_size("10 parsecs").is_err());
+        assert!(byte_size("GiB").is_err());
+        assert!(byte_size("18446744073709551616.5").is_err());
+        assert!(byte_size("99999999999999999999.5 TiB").is_err());
+    }
+
+    #[test]
+    fn parses_booleans_and_lists() {
+        assert!(boolean("Yes").unwrap());
+        assert!(!boolean("off").unwrap());
+        assert!(boolean("maybe").is_err());
+
+        assert_eq!(list::<u16>("80, 443,,8080", ',').unwrap(), [80, 443, 8080]);
+        assert!(list::<u16>("80;http", ';').is_err());
+    }
+}
@@ -0,0 +1,307 @@
+//! Runtime-reloadable configuration.
+//!
+//! Values are wrapped in [`Reloadable`] and re-read from the configuration source on [`reload`], which is triggered by SIGHUP once [`listen`] is running. Valid new values are atomically swapped in and subscribers are notified, while values that cannot change live are reported as requiring a restart.
+
+use std::{
+    fmt::{self, Debug, Display},
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+use tokio::{
+    signal::unix::{SignalKind, signal},
+    sync::watch,
+};
+use tracing::{error, info, warn};
+
+use crate::{env::reload_env_file, static_lazy_lock};
+
+static_lazy_lock! {
+    REGISTRY: Mutex<Vec<&'static (dyn Reload + Sync)>> = Mutex::new(Vec::new());
+}
+
+/// Whether a [`Reloadable`] value can change while the process is running.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReloadPolicy {
+    /// The new value is swapped in and subscribers are notified.
+    Live,
+    /// The current value is kept and the change is reported as requiring a restart.
+    RequiresRestart,
+}
+
+/// The outcome of reloading a single value.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ReloadOutcome {
+    /// The value did not change.
+    Unchanged,
+    /// The value changed and has been swapped in.
+    Reloaded,
+    /// The value changed, but the change only takes effect after a restart.
+    RequiresRestart,
+    /// The new value failed to load or validate, and the current value is kept.
+    Failed(anyhow::Error),
+}
+
+/// A value that can be reloaded from the configuration source.
+pub trait Reload {
+    /// The name of the value, usually the name of its environment variable.
+    fn name(&self) -> &str;
+
+    /// Reloads the value from the configuration source.
+    fn reload(&self) -> ReloadOutcome;
+}
+
+/// A configuration value that is re-read from the configuration source on [`reload`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use api_framework::{env::{parse::optional, reload::{Reloadable, register}}, parse_env, static_lazy_lock};
+/// static_lazy_lock! {
+///     pub WORKERS: Reloadable<u8> = Reloadable::new("WORKERS", || {
+///         Ok(optional(parse_env!("WORKERS" => u8))?.unwrap_or(4))
+///     });
+/// }
+///
+/// // registers the value to be reloaded on SIGHUP
+/// register(&*WORKERS);
+///
+/// let workers = *WORKERS.get();
+/// ```
+pub struct Reloadable<T> {
+    name: &'static str,
+    loader: fn() -> anyhow::Result<T>,
+    policy: ReloadPolicy,
+    sender: watch::Sender<Arc<T>>,
+}
+
+impl<T> Reloadable<T> {
+    /// Creates a live [`Reloadable`] value by loading it from the configuration source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initial value fails to load.
+    pub fn new(name: &'static str, loader: fn() -> anyhow::Result<T>) -> Self {
+        let value = loader().unwrap_or_else(|e| panic!("failed to load {name}: {e:#}"));
+        Self {
+            name,
+            loader,
+            policy: ReloadPolicy::Live,
+            sender: watch::Sender::new(Arc::new(value)),
+        }
+    }
+
+    /// Marks the value as requiring a restart to change.
+    pub fn requires_restart(self) -> Self {
+        Self {
+            policy: ReloadPolicy::RequiresRestart,
+            ..self
+        }
+    }
+
+    /// Gets the current value.
+    pub fn get(&self) -> Arc<T> {
+        self.sender.borrow().clone()
+    }
+
+    /// Subscribes to changes of the value.
+    pub fn subscribe(&self) -> watch::Receiver<Arc<T>> {
+        self.sender.subscribe()
+    }
+}
+
+impl<T> Reload for Reloadable<T>
+where
+    T: PartialEq,
+{
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn reload(&self) -> ReloadOutcome {
+        let value = match (self.loader)() {
+            Ok(value) => value,
+            Err(err) => return ReloadOutcome::Failed(err),
+        };
+
+        if **self.sender.borrow() == value {
+            ReloadOutcome::Unchanged
+        } else {
+            match self.policy {
+                ReloadPolicy::Live => {
+                    self.sender.send_replace(Arc::new(value));
+                    ReloadOutcome::Reloaded
+                }
+                ReloadPolicy::RequiresRestart => ReloadOutcome::RequiresRestart,
+            }
+        }
+    }
+}
+
+impl<T> Debug for Reloadable<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reloadable")
+            .field("name", &self.name)
+            .field("policy", &self.policy)
+            .field("value", &self.get())
+            .finish()
+    }
+}
+
+/// The outcomes of a [`reload`].
+#[derive(Debug, Default)]
+pub struct ReloadReport {
+    /// The outcome of each value, by name.
+    pub outcomes: Vec<(String, ReloadOutcome)>,
+}
+
+impl ReloadReport {
+    /// Iterates over the names of the values that changed but require a restart.
+    pub fn requires_restart(&self) -> impl Iterator<Item = &str> {
+        self.outcomes
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, ReloadOutcome::RequiresRestart))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Checks if any value failed to reload.
+    pub fn has_failures(&self) -> bool {
+        self.outcomes
+            .iter()
+            .any(|(_, outcome)| matches!(outcome, ReloadOutcome::Failed(_)))
+    }
+}
+
+impl Display for ReloadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = |f: fn(&ReloadOutcome) -> bool| {
+            self.outcomes
+                .iter()
+                .filter(|(_, outcome)| f(outcome))
+                .count()
+        };
+        write!(
+            f,
+            "{} reloaded, {} unchanged, {} requiring restart, {} failed",
+            count(|o| matches!(o, ReloadOutcome::Reloaded)),
+            count(|o| matches!(o, ReloadOutcome::Unchanged)),
+            count(|o| matches!(o, ReloadOutcome::RequiresRestart)),
+            count(|o| matches!(o, ReloadOutcome::Failed(_))),
+        )
+    }
+}
+
+/// Registers a value to be reloaded on [`reload`]. Values defined by this crate are registered automatically.
+pub fn register(value: &'static (dyn Reload + Sync)) {
+    REGISTRY.lock().push(value);
+}
+
+/// Re-reads the configuration source and reloads all registered values.
+pub fn reload() -> ReloadReport {
+    info!("reloading configuration…");
+    let mut report = ReloadReport::default();
+
+    if let Err(err) = reload_env_file() {
+        report
+            .outcomes
+            .push((String::from("ENV_FILE"), ReloadOutcome::Failed(err)));
+    }
+
+    let builtins: &[&(dyn Reload + Sync)] = &[
+        #[cfg(feature = "env_max_retries")]
+        &*crate::env::RELOADABLE_MAX_RETRIES,
+        #[cfg(feature = "env_github_token")]
+        &*crate::env::RELOADABLE_GITHUB_TOKEN,
+    ];
+
+    let registry = REGISTRY.lock().clone();
+    for value in builtins.iter().chain(registry.iter()) {
+        let outcome = value.reload();
+        match &outcome {
+            ReloadOutcome::Unchanged => {}
+            ReloadOutcome::Reloaded => info!("reloaded {}", value.name()),
+            ReloadOutcome::RequiresRestart => {
+                warn!(
+                    "{} changed, but requires a restart to take effect",
+                    value.name()
+                )
+            }
+            ReloadOutcome::Failed(err) => {
+                error!(
+                    "failed to reload {}, keeping the current value: {err:#}",
+                    value.name()
+                )
+            }
+        }
+        report.outcomes.push((value.name().to_owned(), outcome));
+    }
+
+    info!("reloaded configuration: {report}");
+    report
+}
+
+/// Reloads the configuration whenever the process receives SIGHUP. Never returns unless the signal handler fails to install.
+///
+/// # Panics
+///
+/// Panics when failed to install SIGHUP signal handler.
+pub async fn listen() {
+    let mut hangup = signal(SignalKind::hangup()).expect("failed to install SIGHUP signal handler");
+    while hangup.recv().await.is_some() {
+        reload();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU8, Ordering};
+
+    use super::*;
+
+    static SOURCE: AtomicU8 = AtomicU8::new(1);
+
+    fn load() -> anyhow::Result<u8> {
+        match SOURCE.load(Ordering::SeqCst) {
+            0 => Err(anyhow::anyhow!("must not be zero")),
+            value => Ok(value),
+        }
+    }
+
+    #[test]
+    fn reloads() {
+        let live = Reloadable::new("LIVE", load);
+        let restart = Reloadable::new("RESTART", load).requires_restart();
+        let receiver = live.subscribe();
+
+        assert!(matches!(live.reload(), ReloadOutcome::Unchanged));
+
+        SOURCE.store(2, Ordering::SeqCst);
+        assert!(matches!(live.reload(), ReloadOutcome::Reloaded));
+        assert!(matches!(restart.reload(), ReloadOutcome::RequiresRestart));
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!(*live.get(), 2);
+        assert_eq!(*restart.get(), 1);
+
+        SOURCE.store(0, Ordering::SeqCst);
+        assert!(matches!(live.reload(), ReloadOutcome::Failed(_)));
+        assert_eq!(*live.get(), 2);
+    }
+
+    #[cfg(feature = "env_github_token")]
+    #[test]
+    fn reports_rotated_github_tokens() {
+        // SAFETY: no other test reads or writes `GITHUB_TOKEN`
+        unsafe { std::env::set_var("GITHUB_TOKEN", "initial") };
+        assert_eq!(crate::env::GITHUB_TOKEN.expose(), "initial");
+
+        unsafe { std::env::set_var("GITHUB_TOKEN", "rotated") };
+        let report = reload();
+        assert!(report.requires_restart().any(|name| name == "GITHUB_TOKEN"));
+        assert_eq!(crate::env::GITHUB_TOKEN.expose(), "initial");
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    fmt::{self, Debug, Display},
+    fs,
+    path::Path,
+};
+
+use anyhow::{Context as _, anyhow};
+use zeroize::Zeroize;
+
+use crate::env;
+
+/// A value that must never be leaked, such as a token or a private key.
+///
+/// The value is redacted when formatted with [`Debug`] or [`Display`], zeroized when dropped, and can only be accessed through [`Secret::expose`].
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    /// Wraps a value into a [`Secret`].
+    pub const fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Exposes the inner value. Be careful not to log or persist it.
+    pub const fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl Secret<String> {
+    /// Loads a secret from the environment variable `key`, or from the file whose path is in the environment variable `{key}_FILE` if the former is not set.
+    ///
+    /// The latter form is compatible with Docker and Kubernetes secrets. Trailing line breaks in the file are trimmed.
+    ///
+    /// See: [`var`](super::var)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither of the variables is set, or the file cannot be read.
+    pub fn from_env(key: &str) -> anyhow::Result<Self> {
+        if let Ok(value) = env::var(key) {
+            return Ok(Self::new(value));
+        }
+
+        let file_key = format!("{key}_FILE");
+        match env::var(&file_key) {
+            Ok(path) => Self::from_file(&path).with_context(|| format!("failed to load {key}")),
+            Err(_) => Err(anyhow!(
+                "neither {key} nor {file_key} is set in environment"
+            )),
+        }
+    }
+
+    /// Loads a secret from a file. Trailing line breaks are trimmed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn from_file<P>(path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut value = fs::read_to_string(path)
+            .with_context(|| format!("failed to read secret from {}", path.display()))?;
+        value.truncate(value.trim_end_matches(['\r', '\n']).len());
+        Ok(Self::new(value))
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Zeroize + Clone> Clone for Secret<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.0.clone())
+    }
+}
+
+impl<T: Zeroize + PartialEq> PartialEq for Secret<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: Zeroize + Eq> Eq for Secret<T> {}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl<T: Zeroize> Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacted() {
+        let secret = Secret::new(String::from("ghp_0123456789"));
+
+        assert_eq!(format!("{secret:?}"), "Secret([REDACTED])");
+        assert_eq!(format!("{secret}"), "[REDACTED]");
+        assert_eq!(secret.expose(), "ghp_0123456789");
+    }
+
+    #[test]
+    fn from_file() {
+        let path = std::env::temp_dir().join("api-framework-secret-from-file");
+        fs::write(&path, "ghp_0123456789\r\n").unwrap();
+
+        let secret = Secret::from_file(&path).unwrap();
+        drop(fs::remove_file(&path));
+
+        assert_eq!(secret.expose(), "ghp_0123456789");
+    }
+}
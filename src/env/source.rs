@@ -0,0 +1,70 @@
+use std::{collections::HashMap, env, fs};
+
+use anyhow::Context as _;
+use parking_lot::RwLock;
+use tracing::{debug, error};
+
+use crate::static_lazy_lock;
+
+static_lazy_lock! {
+    ENV_FILE_VARS: RwLock<HashMap<String, String>> = RwLock::new(
+        read_env_file().unwrap_or_else(|e| {
+            error!("{e:#}");
+            HashMap::new()
+        })
+    );
+}
+
+/// Fetches the environment variable `key` from the configuration source.
+///
+/// The configuration source is the process environment, overlaid by the variables in the file at `ENV_FILE` if set. Unlike the process environment, the file is re-read on [`reload`](super::reload::reload), so variables that need to change at runtime should be put there.
+///
+/// # Errors
+///
+/// Returns an error if the variable is not present in either of them, or is not valid unicode.
+pub fn var<K>(key: K) -> Result<String, env::VarError>
+where
+    K: AsRef<str>,
+{
+    let key = key.as_ref();
+    match ENV_FILE_VARS.read().get(key) {
+        Some(value) => Ok(value.clone()),
+        None => env::var(key),
+    }
+}
+
+/// Re-reads the file at `ENV_FILE`, keeping the previous variables if it fails.
+pub(super) fn reload_env_file() -> anyhow::Result<()> {
+    let vars = read_env_file()?;
+    *ENV_FILE_VARS.write() = vars;
+    Ok(())
+}
+
+fn read_env_file() -> anyhow::Result<HashMap<String, String>> {
+    let Ok(path) = env::var("ENV_FILE") else {
+        return Ok(HashMap::new());
+    };
+
+    debug!("reading environment variables from {path}…");
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("failed to read ENV_FILE at {path}"))?;
+
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line
+                .split_once('=')
+                .with_context(|| format!("invalid line {} in ENV_FILE at {path}", index + 1))?;
+            let value = value.trim();
+            let value = [('"', '"'), ('\'', '\'')]
+                .into_iter()
+                .find_map(|(l, r)| value.strip_prefix(l)?.strip_suffix(r))
+                .unwrap_or(value);
+            Ok((key.trim().to_owned(), value.to_owned()))
+        })
+        .collect()
+}
@@ -0,0 +1,31 @@
+//! Deadlines of businesses, bounding how long transactions may wait, like for rate limits to reset.
+//!
+//! A deadline is visible to everything awaited within [`with_deadline`], without passing it through every transaction.
+
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Runs `future` with a deadline, visible through [`deadline`] and [`remaining`] within it.
+///
+/// The future is not aborted when the deadline passes. See: [`tokio::time::timeout_at`]
+pub async fn with_deadline<F>(deadline: Instant, future: F) -> F::Output
+where
+    F: Future,
+{
+    DEADLINE.scope(deadline, future).await
+}
+
+/// Gets the deadline of the current business, or [`None`] if unbounded.
+pub fn deadline() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Gets the time remaining until the deadline of the current business, or [`None`] if unbounded.
+///
+/// Returns [`Duration::ZERO`] if the deadline has passed.
+pub fn remaining() -> Option<Duration> {
+    deadline().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
@@ -0,0 +1,55 @@
+use crate::framework::StateError;
+
+/// Hooks into the lifecycle of a business, like reporting its progress to an external service.
+///
+/// Hooks cannot fail the business, so they should handle their own errors. Tuples of lifecycles notify each in order, and `()` does nothing.
+///
+/// See: [`QueuedAsyncFramework::run_with_lifecycle`](crate::framework::queued_async::QueuedAsyncFramework::run_with_lifecycle)
+pub trait Lifecycle: Send + Sync {
+    /// Called once the business is queued, before waiting for the ongoing business of the same id.
+    fn queued(&self, name: &str) -> impl Future<Output = ()> + Send {
+        let _ = name;
+        async {}
+    }
+
+    /// Called once the business starts, before the first attempt, within the deadline of the business.
+    ///
+    /// The business of the same id queued next waits for it, so it should be quick.
+    fn started(&self, name: &str) -> impl Future<Output = ()> + Send {
+        let _ = name;
+        async {}
+    }
+
+    /// Called once the business finishes, with its final result.
+    fn finished(
+        &self,
+        name: &str,
+        result: Result<(), StateError>,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (name, result);
+        async {}
+    }
+}
+
+impl Lifecycle for () {}
+
+impl<A, B> Lifecycle for (A, B)
+where
+    A: Lifecycle,
+    B: Lifecycle,
+{
+    async fn queued(&self, name: &str) {
+        self.0.queued(name).await;
+        self.1.queued(name).await;
+    }
+
+    async fn started(&self, name: &str) {
+        self.0.started(name).await;
+        self.1.started(name).await;
+    }
+
+    async fn finished(&self, name: &str, result: Result<(), StateError>) {
+        self.0.finished(name, result).await;
+        self.1.finished(name, result).await;
+    }
+}
@@ -2,8 +2,11 @@
 
 #![cfg(feature = "framework")]
 
+mod lifecycle;
 mod state;
 
+pub mod deadline;
 pub mod queued_async;
 
+pub use lifecycle::*;
 pub use state::*;
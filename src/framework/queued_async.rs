@@ -1,6 +1,6 @@
 //! A framework that loops transactions until the max retry times is reached, or a stop signal is received, or a value is returned.
 
-use crate::framework::{StateError, StateResult};
+use crate::framework::{Lifecycle, StateError, StateResult, deadline::with_deadline};
 
 use super::retry_if_possible;
 
@@ -13,6 +13,7 @@ use std::{
         Arc, LazyLock,
         atomic::{AtomicU8, Ordering},
     },
+    time::{Duration, Instant},
 };
 
 use parking_lot::Mutex;
@@ -31,6 +32,10 @@ pub struct QueuedAsyncFrameworkContext {
     pub index: u8,
     /// The name of the current business. Can be used by loggers to distinguish between businesses.
     pub name: String,
+    /// The deadline of the current business, or [`None`] if unbounded.
+    ///
+    /// See: [`QueuedAsyncFramework::with_timeout`]
+    pub deadline: Option<Instant>,
     holder: Arc<BusinessHolder>,
 }
 
@@ -63,6 +68,7 @@ where
     ID: Eq + Hash,
 {
     businesses: LazyLock<Mutex<HashMap<ID, Arc<BusinessHolder>>>>,
+    timeout: Option<Duration>,
 }
 
 impl<ID> QueuedAsyncFramework<ID>
@@ -73,6 +79,17 @@ where
     pub fn new() -> Self {
         Self {
             businesses: LazyLock::new(|| Mutex::new(HashMap::new())),
+            timeout: None,
+        }
+    }
+
+    /// Creates a [`QueuedAsyncFramework`] whose businesses fail once running for longer than `timeout`, including all retries.
+    ///
+    /// The deadline is visible to the transactions through [`QueuedAsyncFrameworkContext::deadline`] and [`deadline`](crate::framework::deadline::deadline), so that waiting, like for rate limits to reset, can be bounded by it.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..Self::new()
         }
     }
 }
@@ -95,7 +112,7 @@ where
             + Send
             + Sync,
     {
-        let name = format!("{}", &id);
+        let name = format!("{id}");
         self.run_with_name(id, name, f).await
     }
 
@@ -104,46 +121,108 @@ where
     /// # Errors
     ///
     /// Returns the final result of the transaction as-is.
+    ///
+    /// See: [`Self::run_with_lifecycle`]
     pub async fn run_with_name<F, R>(&self, id: ID, name: String, f: F) -> StateResult<R>
     where
         F: Fn(QueuedAsyncFrameworkContext) -> Pin<Box<dyn Future<Output = StateResult<R>> + Send>>
             + Send
             + Sync,
+    {
+        self.run_with_lifecycle(id, name, &(), f).await
+    }
+
+    /// Runs transactions asynchronously with a distinguishable id and a name, notifying a [`Lifecycle`] once the business is queued, started and finished.
+    ///
+    /// The started and finished hooks run while holding the lock of the id, so the next business of the id waits for them. The started hook counts against the deadline of the business.
+    ///
+    /// # Errors
+    ///
+    /// Returns the final result of the transaction as-is.
+    pub async fn run_with_lifecycle<L, F, R>(
+        &self,
+        id: ID,
+        name: String,
+        lifecycle: &L,
+        f: F,
+    ) -> StateResult<R>
+    where
+        L: Lifecycle,
+        F: Fn(QueuedAsyncFrameworkContext) -> Pin<Box<dyn Future<Output = StateResult<R>> + Send>>
+            + Send
+            + Sync,
     {
         let holder = self.businesses.lock().entry(id).or_default().clone();
         let index = holder.latest_payload_index.fetch_add(1, Ordering::SeqCst);
+
+        info!("starting transaction {name}…");
+        let mut retry: u8 = 0;
+        lifecycle.queued(&name).await;
+        let _guard = holder.lock.lock().await;
+
+        // The deadline starts once the business is no longer queued, bounding the started hook as well
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        match deadline {
+            Some(deadline) => with_deadline(deadline, lifecycle.started(&name)).await,
+            None => lifecycle.started(&name).await,
+        }
         let context: QueuedAsyncFrameworkContext = QueuedAsyncFrameworkContext {
             index,
             name: name.clone(),
+            deadline,
             holder: holder.clone(),
         };
 
-        info!("starting transaction {name}…");
-        let mut retry: u8 = 0;
-        let _guard = holder.lock.lock().await;
+        let result = async {
+            loop {
+                let result = match deadline {
+                    Some(deadline) => {
+                        let transaction = with_deadline(deadline, f(context.clone()));
+                        match tokio::time::timeout_at(deadline.into(), transaction).await {
+                            Ok(result) => result,
+                            Err(_) => {
+                                error!("transaction {name} exceeded its deadline!");
+                                return Err(StateError::Retry);
+                            }
+                        }
+                    }
+                    None => f(context.clone()).await,
+                };
 
-        loop {
-            match f(context.clone()).await.and_then(|r| context.check(r)) {
-                Ok(result) => {
-                    info!("transaction {name} succeed!");
-                    holder
-                        .latest_payload_index
-                        .store(u8::default(), Ordering::SeqCst);
-                    return Ok(result);
-                }
-                Err(StateError::Retry) => match retry_if_possible(&mut retry) {
-                    Ok(_) => continue,
-                    Err(_) => {
-                        error!("transaction {name} failed!");
+                match result.and_then(|r| context.check(r)) {
+                    Ok(result) => {
+                        info!("transaction {name} succeed!");
+                        holder
+                            .latest_payload_index
+                            .store(u8::default(), Ordering::SeqCst);
+                        return Ok(result);
+                    }
+                    Err(StateError::Retry)
+                        if deadline.is_some_and(|deadline| Instant::now() >= deadline) =>
+                    {
+                        error!("transaction {name} exceeded its deadline!");
                         return Err(StateError::Retry);
                     }
-                },
-                Err(StateError::Cancelled) => {
-                    error!("transaction {name} cancelled!");
-                    return Err(StateError::Cancelled);
+                    Err(StateError::Retry) => match retry_if_possible(&mut retry) {
+                        Ok(_) => continue,
+                        Err(_) => {
+                            error!("transaction {name} failed!");
+                            return Err(StateError::Retry);
+                        }
+                    },
+                    Err(StateError::Cancelled) => {
+                        error!("transaction {name} cancelled!");
+                        return Err(StateError::Cancelled);
+                    }
                 }
             }
         }
+        .await;
+
+        lifecycle
+            .finished(&name, result.as_ref().map(|_| ()).map_err(|err| *err))
+            .await;
+        result
     }
 }
 
@@ -1,6 +1,6 @@
 use tracing::{error, warn};
 
-use crate::env::MAX_RETRIES;
+use crate::env::max_retries;
 
 /// An error that occurs during state operations.
 #[allow(clippy::exhaustive_enums)]
@@ -25,18 +25,21 @@ pub enum RetryError {
     ExceededMaxRetries,
 }
 
-/// Decides whether retrying is allowed based on a provided retry times and the [`MAX_RETRIES`] environment variable.
+/// Decides whether retrying is allowed based on a provided retry times and the `MAX_RETRIES` environment variable.
+///
+/// See: [`max_retries`]
 ///
 /// # Errors
 ///
 /// [`Err<RetryError>`] is returned if retrying is not allowed, otherwise [`Ok<()>`] is returned.
 pub fn retry_if_possible(retry: &mut u8) -> Result<(), RetryError> {
+    let max_retries = max_retries();
     *retry += 1;
-    if *retry > *MAX_RETRIES {
-        error!("retried for too many times ({}), stopping!", *MAX_RETRIES);
+    if *retry > max_retries {
+        error!("retried for too many times ({max_retries}), stopping!");
         Err(RetryError::ExceededMaxRetries)
     } else {
-        warn!("retrying… ({retry} / {})", *MAX_RETRIES);
+        warn!("retrying… ({retry} / {max_retries})");
         Ok(())
     }
 }
@@ -2,8 +2,10 @@
 
 pub mod env;
 pub mod framework;
+pub mod providers;
 pub mod shutdown;
 pub mod transactions;
+pub mod webhook;
 pub mod workflow;
 
 /// A shorthand to define a statically allocated variable using a [`std::sync::LazyLock`].
@@ -11,6 +13,8 @@ pub mod workflow;
 /// # Examples
 ///
 /// ```rust
+/// # use api_framework::static_lazy_lock;
+/// # use std::sync::LazyLock;
 /// static_lazy_lock!{
 ///     pub VAR_1: String = String::from("a static variable");
 /// }
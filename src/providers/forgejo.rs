@@ -0,0 +1,286 @@
+//! Actions artifacts of Forgejo and Gitea.
+//!
+//! The Actions API of Gitea mirrors the one of GitHub, but trims the workflow runs embedded in artifacts, which are missing their branches.
+
+use std::{
+    fmt::{self, Display},
+    io,
+};
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt as _;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info};
+
+use crate::{
+    env::{self, Secret, parse::optional},
+    framework::{StateError, StateResult},
+    parse_env,
+    providers::{ClientOptions, token_client::TokenClient},
+    transactions::{ArtifactMetadata, ArtifactSource, ArtifactStream, split_repository},
+    workflow::pagination::Page,
+};
+
+/// A client for the REST API of Forgejo or Gitea, authenticating with an access token.
+///
+/// Cloning is cheap and shares the connection pool.
+#[derive(Debug, Clone)]
+pub struct ForgejoClient {
+    client: TokenClient,
+}
+
+impl ForgejoClient {
+    /// Creates a [`ForgejoClient`] for the API at `base_url`, like `https://codeberg.org/api/v1`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the base URL or the token is invalid, or the underlying client fails to build.
+    pub fn new(base_url: &str, token: &Secret<String>) -> anyhow::Result<Self> {
+        Self::with_options(base_url, token, &ClientOptions::default())
+    }
+
+    /// Creates a client like [`Self::new`], configuring the HTTP client with `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the base URL or the token is invalid, or the underlying client fails to build.
+    pub fn with_options(
+        base_url: &str,
+        token: &Secret<String>,
+        options: &ClientOptions,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: TokenClient::new(base_url, &format!("token {}", token.expose()), options)?,
+        })
+    }
+
+    /// Creates a [`ForgejoClient`] from environment variables.
+    ///
+    /// The base URL is loaded from `FORGEJO_API_URL`, and the token from `FORGEJO_TOKEN`. `FORGEJO_TIMEOUT` optionally limits the duration of each request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any variable is missing or invalid, or the client fails to build.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let base_url = env::var("FORGEJO_API_URL")
+            .map_err(|_| anyhow::anyhow!("FORGEJO_API_URL is not set in environment"))?;
+        let mut options = ClientOptions::default();
+        if let Some(timeout) = optional(parse_env!("FORGEJO_TIMEOUT" => duration))? {
+            options = options.timeout(timeout);
+        }
+        Self::with_options(&base_url, &Secret::from_env("FORGEJO_TOKEN")?, &options)
+    }
+
+    /// Gets the base URL of the API, without a trailing slash.
+    pub fn base_url(&self) -> &str {
+        self.client.base_url()
+    }
+}
+
+/// Represents artifacts from the Actions API of Forgejo or Gitea.
+///
+/// Unknown fields are ignored when deserializing.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ForgejoArtifacts {
+    /// The total count of artifacts, across all pages.
+    #[serde(default)]
+    pub total_count: u64,
+    /// The artifacts.
+    pub artifacts: Vec<ForgejoArtifact>,
+}
+
+/// Represents an artifact from the Actions API of Forgejo or Gitea.
+///
+/// Unknown fields are ignored when deserializing.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ForgejoArtifact {
+    /// The unique identifier of the artifact.
+    pub id: u64,
+    /// The name of the artifact.
+    pub name: String,
+    /// The size of the artifact in bytes.
+    #[serde(default)]
+    pub size_in_bytes: u64,
+    /// The URL to download the archive of the artifact.
+    pub archive_download_url: String,
+    /// Whether the artifact has expired.
+    #[serde(default)]
+    pub expired: bool,
+    /// The time when the artifact was created.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// The time when the artifact expires.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// The workflow run that produced the artifact.
+    #[serde(default)]
+    pub workflow_run: Option<ForgejoWorkflowRun>,
+}
+
+/// Represents a workflow run embedded in an artifact.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ForgejoWorkflowRun {
+    /// The unique identifier of the run.
+    pub id: u64,
+    /// The ID of the repository.
+    #[serde(default)]
+    pub repository_id: u64,
+    /// The commit the run was built from.
+    #[serde(default)]
+    pub head_sha: String,
+}
+
+impl ForgejoArtifact {
+    /// Checks if the artifact has expired, either marked by the server or past [`Self::expires_at`].
+    pub fn is_expired(&self) -> bool {
+        self.expired
+            || self
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= Utc::now())
+    }
+}
+
+impl Page for ForgejoArtifacts {
+    type Item = ForgejoArtifact;
+
+    fn into_items(self) -> Vec<Self::Item> {
+        self.artifacts
+    }
+}
+
+impl Display for ForgejoArtifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} at {})",
+            self.name, self.id, self.archive_download_url
+        )
+    }
+}
+
+impl ArtifactSource for ForgejoClient {
+    type Artifact = ForgejoArtifact;
+
+    async fn list(&self, repository: &str, run: Option<&str>) -> StateResult<Vec<ForgejoArtifact>> {
+        let (owner, repo) = split_repository(repository)?;
+        let url = match run {
+            Some(run_id) => self.client.url(&format!(
+                "/repos/{owner}/{repo}/actions/runs/{run_id}/artifacts"
+            )),
+            None => self
+                .client
+                .url(&format!("/repos/{owner}/{repo}/actions/artifacts")),
+        };
+        debug!("listing artifacts from {url}…");
+        self.client.paginate::<ForgejoArtifacts>(&url).await
+    }
+
+    async fn open(&self, artifact: &ForgejoArtifact) -> StateResult<ArtifactStream> {
+        if artifact.is_expired() {
+            error!("refusing to download artifact {artifact}: artifact expired");
+            return Err(StateError::Cancelled);
+        }
+
+        let response = self.client.get(&artifact.archive_download_url).await?;
+        info!("requested download from {}", artifact.archive_download_url);
+        Ok(Box::pin(response.bytes_stream().map_err(io::Error::other)))
+    }
+
+    /// Forgejo and Gitea do not provide digests of artifacts.
+    fn expected_digest(&self, _: &ForgejoArtifact) -> Option<String> {
+        None
+    }
+
+    /// The branch is unknown, so queries by branch never match.
+    fn metadata(&self, artifact: &ForgejoArtifact) -> ArtifactMetadata {
+        let mut metadata = ArtifactMetadata::new(artifact.id.to_string(), artifact.name.clone());
+        metadata.size_in_bytes = Some(artifact.size_in_bytes);
+        metadata.created_at = artifact.created_at;
+        metadata.expires_at = artifact.expires_at;
+        metadata.expired = artifact.expired;
+        metadata.head_sha = artifact
+            .workflow_run
+            .as_ref()
+            .map(|run| run.head_sha.clone());
+        metadata
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write as _};
+
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{header, method, path},
+    };
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::*;
+    use crate::transactions::download_and_extract_from;
+
+    #[tokio::test]
+    async fn lists_and_downloads_run_artifacts() {
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+        archive
+            .start_file("index.html", SimpleFileOptions::default())
+            .unwrap();
+        archive.write_all(b"hello").unwrap();
+        let archive = archive.finish().unwrap().into_inner();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/octocat/hello/actions/runs/2/artifacts"))
+            .and(header("authorization", "token forgejo-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": 1,
+                "artifacts": [{
+                    "id": 1,
+                    "name": "site",
+                    "size_in_bytes": 1000,
+                    "url": format!("{}/api/v1/repos/octocat/hello/actions/artifacts/1", server.uri()),
+                    "archive_download_url": format!("{}/api/v1/repos/octocat/hello/actions/artifacts/1/zip", server.uri()),
+                    "expired": false,
+                    "created_at": "2025-01-01T00:00:00Z",
+                    "workflow_run": { "id": 2, "repository_id": 42, "head_sha": "acb5820" }
+                }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/octocat/hello/actions/artifacts/1/zip"))
+            .and(header("authorization", "token forgejo-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ForgejoClient::new(
+            &format!("{}/api/v1", server.uri()),
+            &Secret::new(String::from("forgejo-token")),
+        )
+        .unwrap();
+        let artifacts = client.list("octocat/hello", Some("2")).await.unwrap();
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(
+            client.metadata(&artifacts[0]).head_sha.as_deref(),
+            Some("acb5820")
+        );
+
+        let dir =
+            std::env::temp_dir().join(format!("api-framework-forgejo-{}", std::process::id()));
+        download_and_extract_from(&client, &artifacts[0], &dir)
+            .await
+            .unwrap();
+        assert_eq!(
+            tokio::fs::read_to_string(dir.join("index.html"))
+                .await
+                .unwrap(),
+            "hello"
+        );
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
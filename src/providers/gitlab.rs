@@ -0,0 +1,378 @@
+//! Job artifacts of GitLab CI.
+//!
+//! Each job with artifacts has a single archive, so a job is an artifact named after it. Pipelines are the runs.
+
+use std::{
+    fmt::{self, Display},
+    io,
+};
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt as _;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info};
+
+use crate::{
+    env::{self, Secret, parse::optional},
+    framework::{StateError, StateResult},
+    parse_env,
+    providers::{ClientOptions, token_client::TokenClient},
+    transactions::{ArtifactMetadata, ArtifactSource, ArtifactStream, LatestArtifactQuery},
+};
+
+/// The default base URL of GitLab REST API.
+pub const DEFAULT_BASE_URL: &str = "https://gitlab.com/api/v4";
+
+/// A client for GitLab REST API, authenticating with a personal, project or group access token.
+///
+/// Cloning is cheap and shares the connection pool.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use api_framework::{env::Secret, framework::StateResult, providers::gitlab::GitLabClient, transactions::{ArtifactSource, LatestArtifactQuery, download_and_extract_from}};
+/// # async fn deploy() -> StateResult<()> {
+/// let client = GitLabClient::new("https://gitlab.example.com/api/v4", &Secret::new(String::from("glpat-…"))).unwrap();
+/// let artifact = client
+///     .resolve("group/site", &LatestArtifactQuery::new("build").branch("main"))
+///     .await?;
+/// download_and_extract_from(&client, &artifact, "/srv/site").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GitLabClient {
+    client: TokenClient,
+}
+
+impl GitLabClient {
+    /// Creates a [`GitLabClient`] for the API at `base_url`, like [`DEFAULT_BASE_URL`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the base URL or the token is invalid, or the underlying client fails to build.
+    pub fn new(base_url: &str, token: &Secret<String>) -> anyhow::Result<Self> {
+        Self::with_options(base_url, token, &ClientOptions::default())
+    }
+
+    /// Creates a client like [`Self::new`], configuring the HTTP client with `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the base URL or the token is invalid, or the underlying client fails to build.
+    pub fn with_options(
+        base_url: &str,
+        token: &Secret<String>,
+        options: &ClientOptions,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: TokenClient::new(base_url, &format!("Bearer {}", token.expose()), options)?,
+        })
+    }
+
+    /// Creates a [`GitLabClient`] from environment variables.
+    ///
+    /// The token is loaded from `GITLAB_TOKEN`, and the base URL is `GITLAB_API_URL` if set, or [`DEFAULT_BASE_URL`]. `GITLAB_TIMEOUT` optionally limits the duration of each request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token is missing, the timeout is invalid, or the client fails to build.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let base_url =
+            env::var("GITLAB_API_URL").unwrap_or_else(|_| String::from(DEFAULT_BASE_URL));
+        let mut options = ClientOptions::default();
+        if let Some(timeout) = optional(parse_env!("GITLAB_TIMEOUT" => duration))? {
+            options = options.timeout(timeout);
+        }
+        Self::with_options(&base_url, &Secret::from_env("GITLAB_TOKEN")?, &options)
+    }
+
+    /// Gets the base URL of GitLab REST API, without a trailing slash.
+    pub fn base_url(&self) -> &str {
+        self.client.base_url()
+    }
+
+    fn project_url(&self, project: &str, path: &str) -> String {
+        // Paths like `group/project` are URL-encoded as a single segment
+        let project = project.replace('/', "%2F");
+        self.client.url(&format!("/projects/{project}/{path}"))
+    }
+}
+
+/// Represents a job of GitLab CI, trimmed to the fields describing its artifacts.
+///
+/// Unknown fields are ignored when deserializing.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GitLabJob {
+    /// The unique identifier of the job.
+    pub id: u64,
+    /// The name of the job.
+    pub name: String,
+    /// The branch or tag the job ran on.
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    /// The status of the job, like `success`.
+    #[serde(default)]
+    pub status: String,
+    /// The commit the job ran on.
+    #[serde(default)]
+    pub commit: Option<GitLabCommit>,
+    /// The time when the job was created.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// The time when the artifacts of the job expire.
+    #[serde(default)]
+    pub artifacts_expire_at: Option<DateTime<Utc>>,
+    /// The archive of the artifacts, if any.
+    #[serde(default)]
+    pub artifacts_file: Option<GitLabArtifactsFile>,
+}
+
+/// Represents a commit of GitLab.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GitLabCommit {
+    /// The SHA of the commit.
+    pub id: String,
+}
+
+/// Represents the archive of the artifacts of a job.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GitLabArtifactsFile {
+    /// The file name of the archive.
+    pub filename: String,
+    /// The size of the archive in bytes.
+    #[serde(default)]
+    pub size: u64,
+}
+
+/// The artifacts of a job in a project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitLabArtifact {
+    /// The ID or the path of the project, like `group/project`.
+    pub project: String,
+    /// The job that produced the artifacts.
+    pub job: GitLabJob,
+}
+
+impl GitLabArtifact {
+    /// Checks if the artifacts have expired.
+    pub fn is_expired(&self) -> bool {
+        self.job
+            .artifacts_expire_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+    }
+}
+
+impl Display for GitLabArtifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (job {} of {})",
+            self.job.name, self.job.id, self.project
+        )
+    }
+}
+
+impl ArtifactSource for GitLabClient {
+    type Artifact = GitLabArtifact;
+
+    /// Lists the artifacts of successful jobs in a project, or in a single pipeline if specified.
+    async fn list(&self, repository: &str, run: Option<&str>) -> StateResult<Vec<GitLabArtifact>> {
+        let path = match run {
+            Some(pipeline_id) => format!("pipelines/{pipeline_id}/jobs"),
+            None => String::from("jobs"),
+        };
+        let url = format!(
+            "{}?scope[]=success&per_page=100",
+            self.project_url(repository, &path)
+        );
+        debug!("listing artifacts from {url}…");
+
+        let jobs = self.client.paginate::<Vec<GitLabJob>>(&url).await?;
+        Ok(jobs
+            .into_iter()
+            .filter(|job| job.artifacts_file.is_some())
+            .map(|job| GitLabArtifact {
+                project: repository.to_owned(),
+                job,
+            })
+            .collect())
+    }
+
+    /// Resolves the artifacts of the most recent successful job that matches the query.
+    ///
+    /// Jobs are listed newest first, so the pages after the first match are not fetched.
+    async fn resolve(
+        &self,
+        repository: &str,
+        query: &LatestArtifactQuery,
+    ) -> StateResult<GitLabArtifact> {
+        let url = format!(
+            "{}?scope[]=success&per_page=100",
+            self.project_url(repository, "jobs")
+        );
+        debug!("resolving artifacts from {url}…");
+
+        let job = self
+            .client
+            .find::<Vec<GitLabJob>, _>(&url, |job| {
+                job.artifacts_file.is_some() && query.matches_metadata(&job_metadata(job))
+            })
+            .await?;
+        match job {
+            Some(job) => {
+                let artifact = GitLabArtifact {
+                    project: repository.to_owned(),
+                    job,
+                };
+                info!("resolved the latest artifact {artifact} of {repository}");
+                Ok(artifact)
+            }
+            None => {
+                error!("no artifacts of {repository} match {query:?}!");
+                Err(StateError::Cancelled)
+            }
+        }
+    }
+
+    async fn open(&self, artifact: &GitLabArtifact) -> StateResult<ArtifactStream> {
+        if artifact.is_expired() {
+            error!("refusing to download artifact {artifact}: artifact expired");
+            return Err(StateError::Cancelled);
+        }
+
+        let url = self.project_url(
+            &artifact.project,
+            &format!("jobs/{}/artifacts", artifact.job.id),
+        );
+        let response = self.client.get(&url).await?;
+        info!("requested download from {url}");
+        Ok(Box::pin(response.bytes_stream().map_err(io::Error::other)))
+    }
+
+    /// GitLab does not provide digests of artifacts.
+    fn expected_digest(&self, _: &GitLabArtifact) -> Option<String> {
+        None
+    }
+
+    fn metadata(&self, artifact: &GitLabArtifact) -> ArtifactMetadata {
+        job_metadata(&artifact.job)
+    }
+}
+
+fn job_metadata(job: &GitLabJob) -> ArtifactMetadata {
+    let mut metadata = ArtifactMetadata::new(job.id.to_string(), job.name.clone());
+    metadata.size_in_bytes = job.artifacts_file.as_ref().map(|file| file.size);
+    metadata.created_at = job.created_at;
+    metadata.expires_at = job.artifacts_expire_at;
+    metadata.head_branch = Some(job.git_ref.clone());
+    metadata.head_sha = job.commit.as_ref().map(|commit| commit.id.clone());
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write as _};
+
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{header, method, path, query_param},
+    };
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::*;
+    use crate::transactions::download_and_extract_from;
+
+    #[tokio::test]
+    async fn resolves_the_newest_job_artifacts_and_downloads_them() {
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+        archive
+            .start_file("index.html", SimpleFileOptions::default())
+            .unwrap();
+        archive.write_all(b"hello").unwrap();
+        let archive = archive.finish().unwrap().into_inner();
+
+        let server = MockServer::start().await;
+        let job = |id: u64, git_ref: &str, created_at: &str, artifacts: bool| {
+            serde_json::json!({
+                "id": id,
+                "name": "build",
+                "ref": git_ref,
+                "status": "success",
+                "commit": { "id": format!("sha{id}") },
+                "created_at": created_at,
+                "artifacts_file": artifacts.then(|| serde_json::json!({ "filename": "artifacts.zip", "size": 1000 })),
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v4/projects/group%2Fsite/jobs"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json([job(
+                1,
+                "main",
+                "2025-01-01T00:00:00Z",
+                true,
+            )]))
+            .expect(0)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v4/projects/group%2Fsite/jobs"))
+            .and(query_param("scope[]", "success"))
+            .and(header("authorization", "Bearer glpat-token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header(
+                        "link",
+                        format!(
+                            "<{}/api/v4/projects/group%2Fsite/jobs?scope[]=success&page=2>; rel=\"next\"",
+                            server.uri()
+                        )
+                        .as_str(),
+                    )
+                    .set_body_json([
+                        job(4, "feature", "2025-01-04T00:00:00Z", true),
+                        job(3, "main", "2025-01-03T00:00:00Z", false),
+                        job(2, "main", "2025-01-02T00:00:00Z", true),
+                    ]),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v4/projects/group%2Fsite/jobs/2/artifacts"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = GitLabClient::new(
+            &format!("{}/api/v4/", server.uri()),
+            &Secret::new(String::from("glpat-token")),
+        )
+        .unwrap();
+        let artifact = client
+            .resolve(
+                "group/site",
+                &LatestArtifactQuery::new("build").branch("main"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(artifact.job.id, 2);
+        assert_eq!(client.metadata(&artifact).head_sha.as_deref(), Some("sha2"));
+
+        let dir = std::env::temp_dir().join(format!("api-framework-gitlab-{}", std::process::id()));
+        download_and_extract_from(&client, &artifact, &dir)
+            .await
+            .unwrap();
+        assert_eq!(
+            tokio::fs::read_to_string(dir.join("index.html"))
+                .await
+                .unwrap(),
+            "hello"
+        );
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
@@ -0,0 +1,59 @@
+//! Artifact sources of CI systems other than GitHub, sharing the extraction and deployment machinery of [`transactions`](crate::transactions).
+//!
+//! See: [`ArtifactSource`](crate::transactions::ArtifactSource)
+
+#![cfg(feature = "providers")]
+
+use std::time::Duration;
+
+use crate::workflow::client::DEFAULT_USER_AGENT;
+
+mod token_client;
+
+pub mod forgejo;
+pub mod gitlab;
+
+/// Options of the HTTP client of a provider, named after the ones of [`GitHubClientBuilder`](crate::workflow::client::GitHubClientBuilder).
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientOptions {
+    /// The user agent sent with every request. Defaults to [`DEFAULT_USER_AGENT`].
+    pub user_agent: String,
+    /// The limit of the duration of each request, from connecting until the response body is read.
+    pub timeout: Option<Duration>,
+    /// The limit of the duration of connecting.
+    pub connect_timeout: Option<Duration>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            user_agent: String::from(DEFAULT_USER_AGENT),
+            timeout: None,
+            connect_timeout: None,
+        }
+    }
+}
+
+impl ClientOptions {
+    /// Uses another user agent. Defaults to [`DEFAULT_USER_AGENT`].
+    pub fn user_agent<S>(mut self, user_agent: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Limits the duration of each request, from connecting until the response body is read.
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Limits the duration of connecting.
+    pub const fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+}
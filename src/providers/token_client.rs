@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use reqwest::{
+    Response, Url,
+    header::{self, HeaderMap, HeaderValue},
+};
+use serde::de::DeserializeOwned;
+use tracing::{debug, error};
+
+use crate::{
+    framework::{StateError, StateResult},
+    providers::ClientOptions,
+    transactions::classify::classify,
+    workflow::{
+        error::error_for_status,
+        pagination::{Page, next_link},
+    },
+};
+
+/// A client for the REST API of a provider, authenticating every request with the `Authorization` header.
+///
+/// The header is marked sensitive, and dropped by [`reqwest`] when following a redirect to another host, like object storage.
+#[derive(Debug, Clone)]
+pub(crate) struct TokenClient {
+    http: reqwest::Client,
+    base_url: Arc<str>,
+}
+
+impl TokenClient {
+    /// Creates a [`TokenClient`] sending `authorization`, like `Bearer {token}`, to the API at `base_url`.
+    pub(crate) fn new(
+        base_url: &str,
+        authorization: &str,
+        options: &ClientOptions,
+    ) -> anyhow::Result<Self> {
+        let base_url = base_url.trim_end_matches('/');
+        Url::parse(base_url).with_context(|| format!("invalid API base URL {base_url}"))?;
+
+        let mut authorization =
+            HeaderValue::from_str(authorization).context("token is not a valid header value")?;
+        authorization.set_sensitive(true);
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        headers.insert(header::AUTHORIZATION, authorization);
+
+        let mut http = reqwest::Client::builder()
+            .default_headers(headers)
+            .user_agent(&options.user_agent);
+        if let Some(timeout) = options.timeout {
+            http = http.timeout(timeout);
+        }
+        if let Some(connect_timeout) = options.connect_timeout {
+            http = http.connect_timeout(connect_timeout);
+        }
+        let http = http.build()?;
+        Ok(Self {
+            http,
+            base_url: base_url.into(),
+        })
+    }
+
+    /// Gets the base URL of the API, without a trailing slash.
+    pub(crate) fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Joins a path to the base URL.
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path.trim_start_matches('/'))
+    }
+
+    /// Sends a GET request to `url`, checking the response status.
+    pub(crate) async fn get(&self, url: &str) -> StateResult<Response> {
+        let response = async {
+            let response = self.http.get(url).send().await?;
+            anyhow::Ok(error_for_status(response).await?)
+        };
+        response.await.map_err(|err| {
+            error!("failed to request {url}: {err:#}");
+            classify(&err)
+        })
+    }
+
+    /// Fetches the items of a paginated list endpoint at `url`, following the `Link: rel="next"` headers until the last page.
+    pub(crate) async fn paginate<P>(&self, url: &str) -> StateResult<Vec<P::Item>>
+    where
+        P: Page + DeserializeOwned,
+        P::Item: Send,
+    {
+        let mut items = Vec::new();
+        let mut next = Some(url.to_owned());
+        while let Some(url) = next {
+            let (page, next_url) = self.page::<P>(&url).await?;
+            items.extend(page.into_items());
+            next = next_url;
+        }
+        Ok(items)
+    }
+
+    /// Finds the first item of a paginated list endpoint at `url` that satisfies `predicate`, without fetching the pages after it.
+    pub(crate) async fn find<P, F>(
+        &self,
+        url: &str,
+        mut predicate: F,
+    ) -> StateResult<Option<P::Item>>
+    where
+        P: Page + DeserializeOwned,
+        P::Item: Send,
+        F: FnMut(&P::Item) -> bool + Send,
+    {
+        let mut next = Some(url.to_owned());
+        while let Some(url) = next {
+            let (page, next_url) = self.page::<P>(&url).await?;
+            if let Some(item) = page.into_items().into_iter().find(&mut predicate) {
+                return Ok(Some(item));
+            }
+            next = next_url;
+        }
+        Ok(None)
+    }
+
+    /// Fetches a page at `url`, along with the URL of the next page if any.
+    async fn page<P>(&self, url: &str) -> StateResult<(P, Option<String>)>
+    where
+        P: Page + DeserializeOwned,
+    {
+        debug!("fetching page {url}…");
+
+        let response = self.get(url).await?;
+        let next = next_link(response.headers());
+        match response.json::<P>().await {
+            Ok(page) => Ok((page, next)),
+            Err(err) => {
+                error!("failed to parse page {url}: {err}");
+                Err(StateError::Retry)
+            }
+        }
+    }
+}
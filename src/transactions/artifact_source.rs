@@ -0,0 +1,348 @@
+use std::{
+    fmt::{Debug, Display},
+    io,
+    path::Path,
+    pin::Pin,
+};
+
+use chrono::{DateTime, Utc};
+use futures::{Stream, TryStreamExt as _};
+use tokio_util::bytes::Bytes;
+use tracing::{debug, error, info};
+
+use crate::{
+    framework::{StateError, StateResult},
+    transactions::{
+        LatestArtifactQuery,
+        download_artifact_and_extract::{expected_digest, extract_verified},
+        download_artifact_with,
+        fetch_artifacts::{artifacts_url, stream_artifacts_from},
+        find_latest_artifact_with,
+    },
+    workflow::{artifact::Artifact, client::GitHubClient},
+};
+
+/// The archive of an artifact, streamed from an [`ArtifactSource`].
+pub type ArtifactStream = Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send>>;
+
+/// The metadata of an artifact, common to all [`ArtifactSource`]s.
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArtifactMetadata {
+    /// The identifier of the artifact, unique within its source.
+    pub id: String,
+    /// The name of the artifact.
+    pub name: String,
+    /// The size of the archive in bytes, if known.
+    pub size_in_bytes: Option<u64>,
+    /// The time when the artifact was created.
+    pub created_at: Option<DateTime<Utc>>,
+    /// The time when the artifact expires.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Whether the artifact has expired.
+    pub expired: bool,
+    /// The branch the artifact was built from.
+    pub head_branch: Option<String>,
+    /// The commit the artifact was built from.
+    pub head_sha: Option<String>,
+}
+
+impl ArtifactMetadata {
+    /// Creates [`ArtifactMetadata`] with an identifier and a name. The other fields are empty.
+    pub fn new<I, S>(id: I, name: S) -> Self
+    where
+        I: Into<String>,
+        S: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Checks if the artifact has expired, either marked by its source or past [`Self::expires_at`].
+    pub fn is_expired(&self) -> bool {
+        self.expired
+            || self
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= Utc::now())
+    }
+}
+
+impl From<&Artifact> for ArtifactMetadata {
+    fn from(artifact: &Artifact) -> Self {
+        let run = artifact.workflow_run.as_ref();
+        Self {
+            id: artifact.id.to_string(),
+            name: artifact.name.clone(),
+            size_in_bytes: Some(artifact.size_in_bytes),
+            created_at: artifact.created_at,
+            expires_at: artifact.expires_at,
+            expired: artifact.expired,
+            head_branch: run.and_then(|run| run.head_branch.clone()),
+            head_sha: run.map(|run| run.head_sha.clone()),
+        }
+    }
+}
+
+/// A source of artifacts, like a CI system or a storage backend, decoupling the extraction and deployment machinery from GitHub.
+///
+/// Repositories and runs are identified by strings in the format of the source, like `octocat/hello` and a workflow run ID for GitHub.
+///
+/// See: [`download_and_extract_from`]
+pub trait ArtifactSource: Send + Sync {
+    /// The artifact of the source.
+    type Artifact: Display + Send + Sync;
+
+    /// Lists the artifacts of a repository, or of a single run if specified.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error that instructs retrying or cancelling if listing the artifacts fails.
+    fn list(
+        &self,
+        repository: &str,
+        run: Option<&str>,
+    ) -> impl Future<Output = StateResult<Vec<Self::Artifact>>> + Send;
+
+    /// Resolves the most recent artifact of a repository that matches the query.
+    ///
+    /// Defaults to filtering [`Self::list`] by [`Self::metadata`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error that instructs retrying or cancelling if listing the artifacts fails, or cancelling if no artifact matches.
+    fn resolve(
+        &self,
+        repository: &str,
+        query: &LatestArtifactQuery,
+    ) -> impl Future<Output = StateResult<Self::Artifact>> + Send {
+        async move {
+            let latest = self
+                .list(repository, None)
+                .await?
+                .into_iter()
+                .map(|artifact| (self.metadata(&artifact), artifact))
+                .filter(|(metadata, _)| query.matches_metadata(metadata))
+                .max_by(|(a, _), (b, _)| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+
+            match latest {
+                Some((_, artifact)) => {
+                    info!("resolved the latest artifact {artifact} of {repository}");
+                    Ok(artifact)
+                }
+                None => {
+                    error!("no artifacts of {repository} match {query:?}!");
+                    Err(StateError::Cancelled)
+                }
+            }
+        }
+    }
+
+    /// Opens the archive of an artifact for streaming.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error that instructs retrying or cancelling if requesting the archive fails, or cancelling if the artifact has expired.
+    fn open(
+        &self,
+        artifact: &Self::Artifact,
+    ) -> impl Future<Output = StateResult<ArtifactStream>> + Send;
+
+    /// Gets the hex-encoded SHA-256 digest the archive is verified against, if provided by the source.
+    fn expected_digest(&self, artifact: &Self::Artifact) -> Option<String>;
+
+    /// Gets the metadata of an artifact.
+    fn metadata(&self, artifact: &Self::Artifact) -> ArtifactMetadata;
+}
+
+impl ArtifactSource for GitHubClient {
+    type Artifact = Artifact;
+
+    async fn list(&self, repository: &str, run: Option<&str>) -> StateResult<Vec<Artifact>> {
+        let (owner, repo) = split_repository(repository)?;
+        let url = match run {
+            Some(run_id) => artifacts_url(self, owner, repo, run_id),
+            None => self.url(&format!("/repos/{owner}/{repo}/actions/artifacts")),
+        };
+        debug!("listing artifacts from {url}…");
+        stream_artifacts_from(self, url).try_collect().await
+    }
+
+    async fn resolve(
+        &self,
+        repository: &str,
+        query: &LatestArtifactQuery,
+    ) -> StateResult<Artifact> {
+        let (owner, repo) = split_repository(repository)?;
+        find_latest_artifact_with(self, owner, repo, query).await
+    }
+
+    async fn open(&self, artifact: &Artifact) -> StateResult<ArtifactStream> {
+        let stream = download_artifact_with(self, artifact).await?;
+        Ok(Box::pin(stream.map_err(io::Error::other)))
+    }
+
+    fn expected_digest(&self, artifact: &Artifact) -> Option<String> {
+        expected_digest(artifact).map(str::to_owned)
+    }
+
+    fn metadata(&self, artifact: &Artifact) -> ArtifactMetadata {
+        artifact.into()
+    }
+}
+
+/// Splits a repository in the format of `owner/repo`.
+pub(crate) fn split_repository(repository: &str) -> StateResult<(&str, &str)> {
+    match repository.split_once('/') {
+        Some((owner, repo)) if !owner.is_empty() && !repo.is_empty() => Ok((owner, repo)),
+        _ => {
+            error!("invalid repository {repository}: expected owner/repo");
+            Err(StateError::Cancelled)
+        }
+    }
+}
+
+/// Downloads an artifact from an [`ArtifactSource`] and extracts the downloaded archive to a specified path.
+///
+/// The archive is verified against [`ArtifactSource::expected_digest`] if provided. Failing to extract or verify removes the path, the same as [`download_artifact_and_extract_with`](crate::transactions::download_artifact_and_extract_with).
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if downloading or extracting the artifact fails, or cancelling if the archive does not match the expected digest.
+pub async fn download_and_extract_from<S, P>(
+    source: &S,
+    artifact: &S::Artifact,
+    path: P,
+) -> StateResult<()>
+where
+    S: ArtifactSource,
+    P: AsRef<Path> + Send + Sync + Debug,
+{
+    match source.open(artifact).await {
+        Ok(stream) => {
+            let digest = source.expected_digest(artifact);
+            extract_verified(artifact, stream, digest.as_deref(), &path).await
+        }
+        Err(err) => {
+            error!("failed to download artifact {artifact}");
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write as _};
+
+    use sha2::Digest as _;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path},
+    };
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::*;
+    use crate::workflow::{WorkflowRun, artifact::Artifacts, client::test_client};
+
+    #[tokio::test]
+    async fn downloads_from_github() {
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+        archive
+            .start_file("index.html", SimpleFileOptions::default())
+            .unwrap();
+        archive.write_all(b"hello").unwrap();
+        let archive = archive.finish().unwrap().into_inner();
+        let digest = hex::encode(sha2::Sha256::digest(&archive));
+
+        let server = MockServer::start().await;
+        let artifact = Artifact::new(
+            1,
+            "site",
+            format!("{}/repos/octocat/hello/actions/artifacts/1", server.uri()),
+        )
+        .with_digest(format!("sha256:{digest}"))
+        .with_workflow_run(WorkflowRun::new(2, 42, "main", "acb5820"));
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello/actions/runs/2/artifacts"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(Artifacts::new(vec![artifact.clone()])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello/actions/artifacts/1/zip"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let artifacts = client.list("octocat/hello", Some("2")).await.unwrap();
+        assert_eq!(artifacts, std::slice::from_ref(&artifact));
+        assert_eq!(client.expected_digest(&artifact), Some(digest));
+        assert_eq!(
+            client.metadata(&artifact).head_branch.as_deref(),
+            Some("main")
+        );
+
+        let dir = std::env::temp_dir().join(format!("api-framework-source-{}", std::process::id()));
+        download_and_extract_from(&client, &artifact, &dir)
+            .await
+            .unwrap();
+        assert_eq!(
+            tokio::fs::read_to_string(dir.join("index.html"))
+                .await
+                .unwrap(),
+            "hello"
+        );
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn fails_on_mismatched_digests() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello/actions/artifacts/1/zip"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"not a zip".as_slice()))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let artifact = Artifact::new(
+            1,
+            "site",
+            client.url("/repos/octocat/hello/actions/artifacts/1"),
+        );
+        let dir =
+            std::env::temp_dir().join(format!("api-framework-corrupt-{}", std::process::id()));
+        assert_eq!(
+            download_and_extract_from(&client, &artifact, &dir).await,
+            Err(StateError::Retry)
+        );
+
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+        archive
+            .start_file("index.html", SimpleFileOptions::default())
+            .unwrap();
+        archive.write_all(b"tampered").unwrap();
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello/actions/artifacts/2/zip"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_bytes(archive.finish().unwrap().into_inner()),
+            )
+            .mount(&server)
+            .await;
+        let artifact = Artifact::new(
+            2,
+            "site",
+            client.url("/repos/octocat/hello/actions/artifacts/2"),
+        )
+        .with_digest(format!("sha256:{}", "0".repeat(64)));
+        assert_eq!(
+            download_and_extract_from(&client, &artifact, &dir).await,
+            Err(StateError::Cancelled)
+        );
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,272 @@
+use parking_lot::Mutex;
+use reqwest::Method;
+use serde::Serialize;
+use tracing::{debug, error, info};
+
+use crate::{
+    framework::{Lifecycle, StateError, StateResult},
+    transactions::classify::classify,
+    workflow::{
+        client::GitHubClient,
+        status::{CheckRun, CheckRunConclusion, CheckRunUpdate},
+    },
+};
+
+#[derive(Serialize)]
+struct NewCheckRun<'a> {
+    name: &'a str,
+    head_sha: &'a str,
+    #[serde(flatten)]
+    update: &'a CheckRunUpdate,
+}
+
+/// Creates a check run for a commit on GitHub using the given client. The client must authenticate as a GitHub App.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if creating the check run fails.
+pub async fn create_check_run(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    name: &str,
+    head_sha: &str,
+    update: &CheckRunUpdate,
+) -> StateResult<CheckRun> {
+    let url = client.url(&format!("/repos/{owner}/{repo}/check-runs"));
+    debug!("creating check run {name} for {head_sha}…");
+
+    let body = NewCheckRun {
+        name,
+        head_sha,
+        update,
+    };
+    match client
+        .send_json::<_, CheckRun>(Method::POST, &url, &body)
+        .await
+    {
+        Ok(check_run) => {
+            info!("created check run {check_run}");
+            Ok(check_run)
+        }
+        Err(err) => {
+            error!("failed to create check run at {url}: {err:#}");
+            Err(classify(&err))
+        }
+    }
+}
+
+/// Updates a check run on GitHub using the given client. The client must authenticate as the GitHub App that created it.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if updating the check run fails.
+pub async fn update_check_run(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    check_run_id: u64,
+    update: &CheckRunUpdate,
+) -> StateResult<CheckRun> {
+    let url = client.url(&format!("/repos/{owner}/{repo}/check-runs/{check_run_id}"));
+    debug!("updating check run {check_run_id}…");
+
+    match client
+        .send_json::<_, CheckRun>(Method::PATCH, &url, update)
+        .await
+    {
+        Ok(check_run) => {
+            info!("updated check run {check_run}");
+            Ok(check_run)
+        }
+        Err(err) => {
+            error!("failed to update check run at {url}: {err:#}");
+            Err(classify(&err))
+        }
+    }
+}
+
+/// Reports the lifecycle of a deployment as a check run of a commit, from queued to in progress to completed with a summary.
+///
+/// The check run is created once queued, and updated afterwards. Failing to report is logged without failing the deployment.
+///
+/// See: [`QueuedAsyncFramework::run_with_lifecycle`](crate::framework::queued_async::QueuedAsyncFramework::run_with_lifecycle)
+#[derive(Debug)]
+pub struct CheckRunReporter {
+    client: GitHubClient,
+    owner: String,
+    repo: String,
+    head_sha: String,
+    name: String,
+    details_url: Option<String>,
+    check_run_id: Mutex<Option<u64>>,
+}
+
+impl CheckRunReporter {
+    /// Creates a [`CheckRunReporter`] for a commit, naming the check run like `Deploy to production`.
+    pub fn new<S>(client: GitHubClient, owner: &str, repo: &str, head_sha: &str, name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            client,
+            owner: owner.to_owned(),
+            repo: repo.to_owned(),
+            head_sha: head_sha.to_owned(),
+            name: name.into(),
+            details_url: None,
+            check_run_id: Mutex::new(None),
+        }
+    }
+
+    /// Links the check run to a URL, like the deployed site.
+    pub fn details_url<S>(mut self, details_url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.details_url = Some(details_url.into());
+        self
+    }
+
+    /// Gets the ID of the check run, once created.
+    pub fn check_run_id(&self) -> Option<u64> {
+        *self.check_run_id.lock()
+    }
+
+    async fn report(&self, mut update: CheckRunUpdate) {
+        if let Some(details_url) = &self.details_url {
+            update = update.details_url(details_url);
+        }
+
+        // Results are already logged
+        match self.check_run_id() {
+            Some(check_run_id) => {
+                drop(
+                    update_check_run(&self.client, &self.owner, &self.repo, check_run_id, &update)
+                        .await,
+                );
+            }
+            None => {
+                if let Ok(check_run) = create_check_run(
+                    &self.client,
+                    &self.owner,
+                    &self.repo,
+                    &self.name,
+                    &self.head_sha,
+                    &update,
+                )
+                .await
+                {
+                    *self.check_run_id.lock() = Some(check_run.id);
+                }
+            }
+        }
+    }
+}
+
+impl Lifecycle for CheckRunReporter {
+    async fn queued(&self, name: &str) {
+        let update = CheckRunUpdate::new().queued().output(
+            "Queued",
+            format!("{name} is waiting for the ongoing deployment."),
+        );
+        self.report(update).await;
+    }
+
+    async fn started(&self, name: &str) {
+        let update = CheckRunUpdate::new()
+            .in_progress()
+            .output("In progress", format!("{name} is in progress."));
+        self.report(update).await;
+    }
+
+    async fn finished(&self, name: &str, result: Result<(), StateError>) {
+        let update = match result {
+            Ok(()) => CheckRunUpdate::new()
+                .completed(CheckRunConclusion::Success)
+                .output(
+                    "Succeeded",
+                    format!("{name} succeeded for `{}`.", self.head_sha),
+                ),
+            Err(StateError::Retry) => CheckRunUpdate::new()
+                .completed(CheckRunConclusion::Failure)
+                .output(
+                    "Failed",
+                    format!("{name} failed after retrying. See the server logs for details."),
+                ),
+            Err(StateError::Cancelled) => CheckRunUpdate::new()
+                .completed(CheckRunConclusion::Cancelled)
+                .output(
+                    "Cancelled",
+                    format!("{name} was cancelled, superseded by a newer deployment or refused."),
+                ),
+        };
+        self.report(update).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{body_partial_json, method, path},
+    };
+
+    use super::*;
+    use crate::{framework::queued_async::QueuedAsyncFramework, workflow::client::test_client};
+
+    #[tokio::test]
+    async fn reports_lifecycle() {
+        let server = MockServer::start().await;
+        let check_run = |status: &str| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": 5,
+                "name": "Deploy",
+                "head_sha": "acb5820",
+                "status": status,
+            }))
+        };
+        Mock::given(method("POST"))
+            .and(path("/repos/octocat/hello/check-runs"))
+            .and(body_partial_json(
+                serde_json::json!({ "name": "Deploy", "head_sha": "acb5820", "status": "queued" }),
+            ))
+            .respond_with(check_run("queued"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/repos/octocat/hello/check-runs/5"))
+            .and(body_partial_json(
+                serde_json::json!({ "status": "in_progress" }),
+            ))
+            .respond_with(check_run("in_progress"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/repos/octocat/hello/check-runs/5"))
+            .and(body_partial_json(
+                serde_json::json!({ "status": "completed", "conclusion": "success" }),
+            ))
+            .respond_with(check_run("completed"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let reporter = CheckRunReporter::new(client, "octocat", "hello", "acb5820", "Deploy");
+        let framework = QueuedAsyncFramework::new();
+        let result = framework
+            .run_with_lifecycle(
+                "octocat/hello",
+                String::from("deployment"),
+                &reporter,
+                |_| Box::pin(async { Ok(()) }),
+            )
+            .await;
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(reporter.check_run_id(), Some(5));
+    }
+}
@@ -0,0 +1,27 @@
+use crate::{
+    framework::StateError,
+    workflow::{auth::AuthError, error::GitHubError},
+};
+
+/// Classifies an error from [`GitHubClient`](crate::workflow::client::GitHubClient) into retrying or cancelling.
+///
+/// See: [`GitHubError`]
+pub(crate) fn classify(err: &anyhow::Error) -> StateError {
+    for cause in err.chain() {
+        if let Some(err) = cause.downcast_ref::<GitHubError>() {
+            return err.into();
+        }
+        if cause.is::<AuthError>() {
+            return StateError::Cancelled;
+        }
+        if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+            return if err.is_connect() || err.is_timeout() || err.is_decode() || err.is_body() {
+                StateError::Retry
+            } else {
+                StateError::Cancelled
+            };
+        }
+    }
+    // Fails to authenticate, like signing a JWT
+    StateError::Retry
+}
@@ -0,0 +1,233 @@
+use reqwest::Method;
+use serde::de::IgnoredAny;
+use tracing::{debug, error, info};
+
+use crate::{
+    framework::{Lifecycle, StateError, StateResult},
+    transactions::classify::classify,
+    workflow::{
+        client::GitHubClient,
+        status::{CommitState, CommitStatus},
+    },
+};
+
+/// Creates a commit status for a commit on GitHub using the given client.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if creating the commit status fails.
+pub async fn create_commit_status(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    sha: &str,
+    status: &CommitStatus,
+) -> StateResult<()> {
+    let url = client.url(&format!("/repos/{owner}/{repo}/statuses/{sha}"));
+    debug!(
+        "creating {:?} commit status {} for {sha}…",
+        status.state, status.context
+    );
+
+    match client
+        .send_json::<_, IgnoredAny>(Method::POST, &url, status)
+        .await
+    {
+        Ok(_) => {
+            info!(
+                "created {:?} commit status {} for {sha}",
+                status.state, status.context
+            );
+            Ok(())
+        }
+        Err(err) => {
+            error!("failed to create commit status at {url}: {err:#}");
+            Err(classify(&err))
+        }
+    }
+}
+
+/// Reports the lifecycle of a deployment as commit statuses of a commit, from pending to success, or error if it failed or was cancelled, like when superseded by a newer deployment.
+///
+/// Failing to report is logged without failing the deployment.
+///
+/// See: [`QueuedAsyncFramework::run_with_lifecycle`](crate::framework::queued_async::QueuedAsyncFramework::run_with_lifecycle)
+#[derive(Debug, Clone)]
+pub struct CommitStatusReporter {
+    client: GitHubClient,
+    owner: String,
+    repo: String,
+    sha: String,
+    context: String,
+    target_url: Option<String>,
+}
+
+impl CommitStatusReporter {
+    /// Creates a [`CommitStatusReporter`] for a commit, reporting in the context like `deploy/production`.
+    pub fn new<S>(client: GitHubClient, owner: &str, repo: &str, sha: &str, context: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            client,
+            owner: owner.to_owned(),
+            repo: repo.to_owned(),
+            sha: sha.to_owned(),
+            context: context.into(),
+            target_url: None,
+        }
+    }
+
+    /// Links the statuses to a URL, like the deployed site.
+    pub fn target_url<S>(mut self, target_url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.target_url = Some(target_url.into());
+        self
+    }
+
+    async fn report(&self, state: CommitState, description: &str) {
+        let mut status = CommitStatus::new(state, &self.context).description(description);
+        if let Some(target_url) = &self.target_url {
+            status = status.target_url(target_url);
+        }
+        // Already logged
+        let _ =
+            create_commit_status(&self.client, &self.owner, &self.repo, &self.sha, &status).await;
+    }
+}
+
+impl Lifecycle for CommitStatusReporter {
+    async fn queued(&self, name: &str) {
+        self.report(CommitState::Pending, &format!("{name} is queued"))
+            .await;
+    }
+
+    async fn started(&self, name: &str) {
+        self.report(CommitState::Pending, &format!("{name} is in progress"))
+            .await;
+    }
+
+    async fn finished(&self, name: &str, result: Result<(), StateError>) {
+        match result {
+            Ok(()) => {
+                self.report(CommitState::Success, &format!("{name} succeeded"))
+                    .await;
+            }
+            Err(StateError::Retry) => {
+                self.report(CommitState::Error, &format!("{name} failed after retrying"))
+                    .await;
+            }
+            Err(StateError::Cancelled) => {
+                self.report(
+                    CommitState::Error,
+                    &format!("{name} was cancelled or superseded"),
+                )
+                .await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{body_partial_json, method, path},
+    };
+
+    use super::*;
+    use crate::{framework::queued_async::QueuedAsyncFramework, workflow::client::test_client};
+
+    fn created(state: &str) -> ResponseTemplate {
+        ResponseTemplate::new(201).set_body_json(serde_json::json!({
+            "url": "https://api.github.com/repos/octocat/Hello-World/statuses/6dcb09b5b57875f334f61aebed695e2e4193db5e",
+            "id": 1,
+            "node_id": "MDY6U3RhdHVzMQ==",
+            "state": state,
+            "description": "Build has completed successfully",
+            "target_url": "https://ci.example.com/1000/output",
+            "context": "continuous-integration/jenkins",
+            "created_at": "2012-07-20T01:19:13Z",
+            "updated_at": "2012-07-20T01:19:13Z"
+        }))
+    }
+
+    #[tokio::test]
+    async fn creates_commit_statuses() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/repos/octocat/hello/statuses/acb5820"))
+            .and(body_partial_json(serde_json::json!({
+                "state": "success",
+                "context": "deploy/production",
+                "description": "deployed",
+                "target_url": "https://example.com",
+            })))
+            .respond_with(created("success"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repos/octocat/hello/statuses/0000000"))
+            .respond_with(ResponseTemplate::new(422).set_body_json(serde_json::json!({
+                "message": "No commit found for SHA: 0000000",
+                "documentation_url": "https://docs.github.com/rest/commits/statuses#create-a-commit-status"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let status = CommitStatus::new(CommitState::Success, "deploy/production")
+            .description("deployed")
+            .target_url("https://example.com");
+        assert_eq!(
+            create_commit_status(&client, "octocat", "hello", "acb5820", &status).await,
+            Ok(())
+        );
+        assert_eq!(
+            create_commit_status(&client, "octocat", "hello", "0000000", &status).await,
+            Err(StateError::Cancelled)
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_superseded_deployments_as_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/repos/octocat/hello/statuses/acb5820"))
+            .and(body_partial_json(
+                serde_json::json!({ "state": "pending", "context": "deploy/production" }),
+            ))
+            .respond_with(created("pending"))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repos/octocat/hello/statuses/acb5820"))
+            .and(body_partial_json(serde_json::json!({
+                "state": "error",
+                "description": "deployment was cancelled or superseded",
+            })))
+            .respond_with(created("error"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let reporter =
+            CommitStatusReporter::new(client, "octocat", "hello", "acb5820", "deploy/production");
+        let framework = QueuedAsyncFramework::new();
+        let result: StateResult<()> = framework
+            .run_with_lifecycle(
+                "octocat/hello",
+                String::from("deployment"),
+                &reporter,
+                |_| Box::pin(async { Err(StateError::Cancelled) }),
+            )
+            .await;
+
+        assert_eq!(result, Err(StateError::Cancelled));
+    }
+}
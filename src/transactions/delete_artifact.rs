@@ -0,0 +1,169 @@
+use chrono::{TimeDelta, Utc};
+use futures::TryStreamExt as _;
+use reqwest::{Method, StatusCode};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    framework::StateResult,
+    transactions::{classify::classify, fetch_artifacts::stream_artifacts_from},
+    workflow::{
+        artifact::Artifact,
+        client::{GITHUB_CLIENT, GitHubClient},
+        error::{GitHubError, error_for_status},
+    },
+};
+
+/// Deletes an [`Artifact`] from GitHub using the shared [`GITHUB_CLIENT`], freeing its storage. An already deleted artifact is skipped.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if deleting the artifact fails.
+///
+/// See: [`delete_artifact_with`]
+pub async fn delete_artifact(artifact: &Artifact) -> StateResult<()> {
+    delete_artifact_with(&GITHUB_CLIENT, artifact).await
+}
+
+/// Deletes an [`Artifact`] from GitHub using the given client, freeing its storage. An already deleted artifact is skipped.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if deleting the artifact fails.
+pub async fn delete_artifact_with(client: &GitHubClient, artifact: &Artifact) -> StateResult<()> {
+    debug!("deleting artifact {artifact}…");
+
+    let result = async {
+        let response = client
+            .request(Method::DELETE, &artifact.url)
+            .await?
+            .send()
+            .await?;
+        anyhow::Ok(error_for_status(response).await?)
+    };
+    match result.await {
+        Ok(_) => {
+            info!("deleted artifact {artifact}");
+            Ok(())
+        }
+        Err(err)
+            if err
+                .downcast_ref::<GitHubError>()
+                .is_some_and(|err| err.status == StatusCode::NOT_FOUND) =>
+        {
+            warn!("artifact {artifact} is already deleted");
+            Ok(())
+        }
+        Err(err) => {
+            error!("failed to delete artifact {artifact}: {err:#}");
+            Err(classify(&err))
+        }
+    }
+}
+
+/// Deletes all artifacts older than `max_age` across a repository from GitHub using the shared [`GITHUB_CLIENT`].
+///
+/// The age is like `TimeDelta::days(7)`. Expired artifacts and artifacts without creation times are skipped.
+///
+/// Returns the number of deleted artifacts.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if listing or deleting the artifacts fails. Artifacts deleted before the failure stay deleted.
+///
+/// See: [`prune_artifacts_with`]
+pub async fn prune_artifacts(owner: &str, repo: &str, max_age: TimeDelta) -> StateResult<u64> {
+    prune_artifacts_with(&GITHUB_CLIENT, owner, repo, max_age).await
+}
+
+/// Deletes all artifacts older than `max_age` across a repository from GitHub using the given client, like `TimeDelta::days(7)`. Expired artifacts and artifacts without creation times are skipped.
+///
+/// Returns the number of deleted artifacts.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if listing or deleting the artifacts fails. Artifacts deleted before the failure stay deleted.
+pub async fn prune_artifacts_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    max_age: TimeDelta,
+) -> StateResult<u64> {
+    let url = client.url(&format!("/repos/{owner}/{repo}/actions/artifacts"));
+    let threshold = Utc::now() - max_age;
+    debug!("pruning artifacts created before {threshold} from {url}…");
+
+    // Collects before deleting, since deleting shifts the pages
+    let artifacts: Vec<Artifact> = stream_artifacts_from(client, url.clone())
+        .try_filter(|artifact| {
+            futures::future::ready(
+                !artifact.is_expired()
+                    && artifact
+                        .created_at
+                        .is_some_and(|created_at| created_at < threshold),
+            )
+        })
+        .try_collect()
+        .await?;
+
+    let mut deleted = 0;
+    for artifact in &artifacts {
+        delete_artifact_with(client, artifact).await?;
+        deleted += 1;
+    }
+
+    info!("pruned {deleted} artifacts created before {threshold} from {url}");
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path},
+    };
+
+    use super::*;
+    use crate::workflow::{artifact::Artifacts, client::test_client};
+
+    #[tokio::test]
+    async fn prunes_old_artifacts() {
+        let server = MockServer::start().await;
+        let artifact = |id: u64, age: TimeDelta| {
+            Artifact::new(
+                id,
+                format!("site-{id}"),
+                format!(
+                    "{}/repos/octocat/hello/actions/artifacts/{id}",
+                    server.uri()
+                ),
+            )
+            .with_created_at(Utc::now() - age)
+        };
+        let mut expired = artifact(3, TimeDelta::days(90));
+        expired.expired = true;
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello/actions/artifacts"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(Artifacts::new(vec![
+                    artifact(1, TimeDelta::days(30)),
+                    artifact(2, TimeDelta::hours(1)),
+                    expired,
+                ])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/repos/octocat/hello/actions/artifacts/1"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let pruned = prune_artifacts_with(&client, "octocat", "hello", TimeDelta::days(7))
+            .await
+            .unwrap();
+
+        assert_eq!(pruned, 1);
+    }
+}
@@ -0,0 +1,381 @@
+use parking_lot::Mutex;
+use reqwest::Method;
+use serde::{Deserialize, de::IgnoredAny};
+use tracing::{debug, error, info};
+
+use crate::{
+    framework::{Lifecycle, StateError, StateResult},
+    transactions::classify::classify,
+    workflow::{
+        client::GitHubClient,
+        deployment::{Deployment, DeploymentState, DeploymentStatus, NewDeployment},
+    },
+};
+
+/// Creates a deployment on GitHub using the given client.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if creating the deployment fails, or cancelling if GitHub merged the default branch into the ref instead, as requested by [`NewDeployment::auto_merge`], since the ref no longer points to the commit to deploy.
+pub async fn create_deployment(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    deployment: &NewDeployment,
+) -> StateResult<Deployment> {
+    let url = client.url(&format!("/repos/{owner}/{repo}/deployments"));
+    debug!(
+        "creating deployment of {} to {}…",
+        deployment.git_ref, deployment.environment
+    );
+
+    match client
+        .send_json::<_, Created>(Method::POST, &url, deployment)
+        .await
+    {
+        Ok(Created::Deployment(deployment)) => {
+            info!("created deployment {deployment}");
+            Ok(deployment)
+        }
+        Ok(Created::Merged { message }) => {
+            error!(
+                "GitHub merged the default branch into {} instead of creating the deployment: {message}",
+                deployment.git_ref
+            );
+            Err(StateError::Cancelled)
+        }
+        Err(err) => {
+            error!("failed to create deployment at {url}: {err:#}");
+            Err(classify(&err))
+        }
+    }
+}
+
+/// The reply to creating a deployment, which is `202 Accepted` with a message if the default branch is merged into the ref first.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Created {
+    Deployment(Deployment),
+    Merged { message: String },
+}
+
+/// Creates a status of a deployment on GitHub using the given client.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if creating the deployment status fails.
+pub async fn create_deployment_status(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    deployment_id: u64,
+    status: &DeploymentStatus,
+) -> StateResult<()> {
+    let url = client.url(&format!(
+        "/repos/{owner}/{repo}/deployments/{deployment_id}/statuses"
+    ));
+    debug!(
+        "creating {:?} status of deployment {deployment_id}…",
+        status.state
+    );
+
+    match client
+        .send_json::<_, IgnoredAny>(Method::POST, &url, status)
+        .await
+    {
+        Ok(_) => {
+            info!(
+                "created {:?} status of deployment {deployment_id}",
+                status.state
+            );
+            Ok(())
+        }
+        Err(err) => {
+            error!("failed to create deployment status at {url}: {err:#}");
+            Err(classify(&err))
+        }
+    }
+}
+
+/// Marks a deployment on GitHub as inactive using the given client, like when its environment is torn down.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if creating the deployment status fails.
+///
+/// See: [`create_deployment_status`]
+pub async fn deactivate_deployment(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    deployment_id: u64,
+) -> StateResult<()> {
+    let status = DeploymentStatus::new(DeploymentState::Inactive);
+    create_deployment_status(client, owner, repo, deployment_id, &status).await
+}
+
+/// Reports the lifecycle of a deployment as a deployment on GitHub, shown on the environment page of the repository.
+///
+/// The deployment is created once queued, and its status is updated afterwards, recording the environment URL once succeeded. Failing to report is logged without failing the deployment.
+///
+/// See: [`QueuedAsyncFramework::run_with_lifecycle`](crate::framework::queued_async::QueuedAsyncFramework::run_with_lifecycle)
+#[derive(Debug)]
+pub struct DeploymentReporter {
+    client: GitHubClient,
+    owner: String,
+    repo: String,
+    deployment: NewDeployment,
+    environment_url: Option<String>,
+    log_url: Option<String>,
+    deployment_id: Mutex<Option<u64>>,
+}
+
+impl DeploymentReporter {
+    /// Creates a [`DeploymentReporter`] creating `deployment` in a repository.
+    pub fn new(client: GitHubClient, owner: &str, repo: &str, deployment: NewDeployment) -> Self {
+        Self {
+            client,
+            owner: owner.to_owned(),
+            repo: repo.to_owned(),
+            deployment,
+            environment_url: None,
+            log_url: None,
+            deployment_id: Mutex::new(None),
+        }
+    }
+
+    /// Records the URL of the deployed environment, like the deployed site.
+    pub fn environment_url<S>(mut self, environment_url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.environment_url = Some(environment_url.into());
+        self
+    }
+
+    /// Links the statuses to the logs of the deployment.
+    pub fn log_url<S>(mut self, log_url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.log_url = Some(log_url.into());
+        self
+    }
+
+    /// Gets the ID of the deployment, once created.
+    pub fn deployment_id(&self) -> Option<u64> {
+        *self.deployment_id.lock()
+    }
+
+    async fn report(&self, state: DeploymentState, description: String) {
+        let deployment_id = match self.deployment_id() {
+            Some(deployment_id) => deployment_id,
+            None => {
+                match create_deployment(&self.client, &self.owner, &self.repo, &self.deployment)
+                    .await
+                {
+                    Ok(deployment) => {
+                        *self.deployment_id.lock() = Some(deployment.id);
+                        deployment.id
+                    }
+                    // Already logged
+                    Err(_) => return,
+                }
+            }
+        };
+
+        let mut status = DeploymentStatus::new(state).description(description);
+        if let Some(log_url) = &self.log_url {
+            status = status.log_url(log_url);
+        }
+        if state == DeploymentState::Success
+            && let Some(environment_url) = &self.environment_url
+        {
+            status = status.environment_url(environment_url);
+        }
+        // Already logged
+        let _ = create_deployment_status(
+            &self.client,
+            &self.owner,
+            &self.repo,
+            deployment_id,
+            &status,
+        )
+        .await;
+    }
+}
+
+impl Lifecycle for DeploymentReporter {
+    async fn queued(&self, name: &str) {
+        self.report(DeploymentState::Queued, format!("{name} is queued"))
+            .await;
+    }
+
+    async fn started(&self, name: &str) {
+        self.report(
+            DeploymentState::InProgress,
+            format!("{name} is in progress"),
+        )
+        .await;
+    }
+
+    async fn finished(&self, name: &str, result: Result<(), StateError>) {
+        let (state, description) = match result {
+            Ok(()) => (DeploymentState::Success, format!("{name} succeeded")),
+            Err(StateError::Retry) => (
+                DeploymentState::Error,
+                format!("{name} failed after retrying"),
+            ),
+            Err(StateError::Cancelled) => (
+                DeploymentState::Error,
+                format!("{name} was cancelled or superseded"),
+            ),
+        };
+        self.report(state, description).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{body_partial_json, method, path},
+    };
+
+    use super::*;
+    use crate::{framework::queued_async::QueuedAsyncFramework, workflow::client::test_client};
+
+    #[tokio::test]
+    async fn reports_lifecycle() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/repos/octocat/hello/deployments"))
+            .and(body_partial_json(serde_json::json!({
+                "ref": "acb5820",
+                "environment": "production",
+                "auto_merge": false,
+                "required_contexts": [],
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": 9,
+                "sha": "acb5820",
+                "ref": "acb5820",
+                "environment": "production",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        for state in ["queued", "in_progress"] {
+            Mock::given(method("POST"))
+                .and(path("/repos/octocat/hello/deployments/9/statuses"))
+                .and(body_partial_json(serde_json::json!({ "state": state })))
+                .respond_with(ResponseTemplate::new(201).set_body_string("{}"))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/repos/octocat/hello/deployments/9/statuses"))
+            .and(body_partial_json(serde_json::json!({
+                "state": "success",
+                "environment_url": "https://hello.example.com",
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_string("{}"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let reporter = DeploymentReporter::new(
+            client,
+            "octocat",
+            "hello",
+            NewDeployment::new("acb5820", "production"),
+        )
+        .environment_url("https://hello.example.com");
+        let framework = QueuedAsyncFramework::new();
+        let result = framework
+            .run_with_lifecycle(
+                "octocat/hello",
+                String::from("deployment"),
+                &reporter,
+                |_| Box::pin(async { Ok(()) }),
+            )
+            .await;
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(reporter.deployment_id(), Some(9));
+    }
+
+    #[tokio::test]
+    async fn cancels_auto_merged_deployments() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/repos/octocat/hello/deployments"))
+            .and(body_partial_json(serde_json::json!({ "auto_merge": true })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "message": "Auto-merged master into topic-branch on deployment."
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let mut deployment = NewDeployment::new("topic-branch", "production");
+        deployment.auto_merge = true;
+        assert_eq!(
+            create_deployment(&client, "octocat", "hello", &deployment).await,
+            Err(StateError::Cancelled)
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_superseded_deployments_as_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/repos/octocat/hello/deployments"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": 9,
+                "sha": "acb5820",
+                "ref": "acb5820",
+                "environment": "production",
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repos/octocat/hello/deployments/9/statuses"))
+            .respond_with(ResponseTemplate::new(201).set_body_string("{}"))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repos/octocat/hello/deployments/9/statuses"))
+            .and(body_partial_json(serde_json::json!({
+                "state": "error",
+                "description": "deployment was cancelled or superseded",
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_string("{}"))
+            .expect(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let reporter = DeploymentReporter::new(
+            client,
+            "octocat",
+            "hello",
+            NewDeployment::new("acb5820", "production"),
+        );
+        let framework = QueuedAsyncFramework::new();
+        let result: StateResult<()> = framework
+            .run_with_lifecycle(
+                "octocat/hello",
+                String::from("deployment"),
+                &reporter,
+                |_| Box::pin(async { Err(StateError::Cancelled) }),
+            )
+            .await;
+
+        assert_eq!(result, Err(StateError::Cancelled));
+    }
+}
@@ -0,0 +1,367 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::TryStreamExt as _;
+use reqwest::Method;
+use serde::Serialize;
+use tracing::{debug, error, info};
+
+use crate::{
+    framework::StateResult,
+    transactions::{WorkflowRunQuery, classify::classify, list_workflow_runs_with, wait::poll},
+    workflow::{
+        WorkflowRun,
+        client::{GITHUB_CLIENT, GitHubClient},
+    },
+};
+
+/// How much earlier than the dispatch a dispatched run may appear to be created, due to clock skew.
+const CLOCK_SKEW: TimeDelta = TimeDelta::seconds(5);
+
+#[derive(Serialize)]
+struct Dispatch<'a> {
+    #[serde(rename = "ref")]
+    git_ref: &'a str,
+    inputs: BTreeMap<&'a str, &'a str>,
+}
+
+/// Dispatches a workflow on GitHub using the shared [`GITHUB_CLIENT`]. Returns the time of the dispatch, to find the dispatched run with [`find_dispatched_run`].
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if dispatching fails.
+///
+/// See: [`dispatch_workflow_with`]
+pub async fn dispatch_workflow(
+    owner: &str,
+    repo: &str,
+    workflow: &str,
+    git_ref: &str,
+    inputs: &[(&str, &str)],
+) -> StateResult<DateTime<Utc>> {
+    dispatch_workflow_with(&GITHUB_CLIENT, owner, repo, workflow, git_ref, inputs).await
+}
+
+/// Dispatches a workflow on GitHub using the given client. Returns the time of the dispatch, to find the dispatched run with [`find_dispatched_run_with`].
+///
+/// The workflow is either the file name of the workflow, like `deploy.yml`, or its ID, and must be triggered by `workflow_dispatch` with the given inputs. The ref is a branch or a tag.
+///
+/// Dispatching and finding the run are separate, so that retrying to find the run, like after reaching the deadline, does not dispatch the workflow again. Keep the returned time across retries.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if dispatching fails.
+pub async fn dispatch_workflow_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    workflow: &str,
+    git_ref: &str,
+    inputs: &[(&str, &str)],
+) -> StateResult<DateTime<Utc>> {
+    let url = client.url(&format!(
+        "/repos/{owner}/{repo}/actions/workflows/{workflow}/dispatches"
+    ));
+    debug!("dispatching {workflow} on {git_ref}…");
+
+    let dispatched_at = Utc::now();
+    let body = Dispatch {
+        git_ref,
+        inputs: inputs.iter().copied().collect(),
+    };
+    match client.send_body(Method::POST, &url, &body).await {
+        Ok(_) => {
+            info!("dispatched {workflow} on {git_ref}");
+            Ok(dispatched_at)
+        }
+        Err(err) => {
+            error!("failed to dispatch workflow at {url}: {err:#}");
+            Err(classify(&err))
+        }
+    }
+}
+
+/// Finds the run of a workflow dispatched on a ref at `dispatched_at` on GitHub using the shared [`GITHUB_CLIENT`], polling every `interval` until it appears.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if listing the runs fails, or retrying if the deadline is reached before the run appears.
+///
+/// See: [`find_dispatched_run_with`]
+pub async fn find_dispatched_run(
+    owner: &str,
+    repo: &str,
+    workflow: &str,
+    git_ref: &str,
+    dispatched_at: DateTime<Utc>,
+    interval: Duration,
+) -> StateResult<WorkflowRun> {
+    find_dispatched_run_with(
+        &GITHUB_CLIENT,
+        owner,
+        repo,
+        workflow,
+        git_ref,
+        dispatched_at,
+        interval,
+    )
+    .await
+}
+
+/// Finds the run of a workflow dispatched on a ref at `dispatched_at` on GitHub using the given client, polling every `interval` until it appears.
+///
+/// GitHub does not tell the dispatched run, so it is the newest run of the ref triggered by `workflow_dispatch` since dispatching. Concurrent dispatches of the same workflow on the same ref may be confused.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if listing the runs fails, or retrying if the deadline is reached before the run appears.
+///
+/// See: [`dispatch_workflow_with`], [`wait_for_workflow_run_with`](crate::transactions::wait_for_workflow_run_with)
+pub async fn find_dispatched_run_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    workflow: &str,
+    git_ref: &str,
+    dispatched_at: DateTime<Utc>,
+    interval: Duration,
+) -> StateResult<WorkflowRun> {
+    // Runs are filtered by the short name of the branch or the tag
+    let branch = git_ref
+        .strip_prefix("refs/heads/")
+        .or_else(|| git_ref.strip_prefix("refs/tags/"))
+        .unwrap_or(git_ref);
+    let query = WorkflowRunQuery::new()
+        .event("workflow_dispatch")
+        .branch(branch)
+        .created_since(dispatched_at - CLOCK_SKEW);
+    let run = poll(
+        &format!("the dispatched run of {workflow} on {git_ref}"),
+        interval,
+        || {
+            let runs = list_workflow_runs_with(client, owner, repo, workflow, &query);
+            async move {
+                futures::pin_mut!(runs);
+                runs.try_next().await
+            }
+        },
+    )
+    .await?;
+
+    info!("found the dispatched run {run} of {workflow} on {git_ref}");
+    Ok(run)
+}
+
+/// Re-runs all jobs of a workflow run on GitHub using the shared [`GITHUB_CLIENT`], as a new attempt of the same run.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if re-running fails.
+///
+/// See: [`rerun_workflow_run_with`]
+pub async fn rerun_workflow_run(owner: &str, repo: &str, run_id: &str) -> StateResult<()> {
+    rerun_workflow_run_with(&GITHUB_CLIENT, owner, repo, run_id).await
+}
+
+/// Re-runs all jobs of a workflow run on GitHub using the given client, as a new attempt of the same run.
+///
+/// Wait for the new attempt by passing the attempt before re-running to [`wait_for_workflow_run_with`](crate::transactions::wait_for_workflow_run_with).
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if re-running fails.
+///
+/// See: [`wait_for_workflow_run_with`](crate::transactions::wait_for_workflow_run_with)
+pub async fn rerun_workflow_run_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+) -> StateResult<()> {
+    rerun(client, owner, repo, run_id, "rerun").await
+}
+
+/// Re-runs the failed jobs of a workflow run and their dependents on GitHub using the shared [`GITHUB_CLIENT`], as a new attempt of the same run.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if re-running fails.
+///
+/// See: [`rerun_failed_jobs_with`]
+pub async fn rerun_failed_jobs(owner: &str, repo: &str, run_id: &str) -> StateResult<()> {
+    rerun_failed_jobs_with(&GITHUB_CLIENT, owner, repo, run_id).await
+}
+
+/// Re-runs the failed jobs of a workflow run and their dependents on GitHub using the given client, as a new attempt of the same run.
+///
+/// Wait for the new attempt by passing the attempt before re-running to [`wait_for_workflow_run_with`](crate::transactions::wait_for_workflow_run_with).
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if re-running fails.
+///
+/// See: [`wait_for_workflow_run_with`](crate::transactions::wait_for_workflow_run_with)
+pub async fn rerun_failed_jobs_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+) -> StateResult<()> {
+    rerun(client, owner, repo, run_id, "rerun-failed-jobs").await
+}
+
+async fn rerun(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+    endpoint: &str,
+) -> StateResult<()> {
+    let url = client.url(&format!(
+        "/repos/{owner}/{repo}/actions/runs/{run_id}/{endpoint}"
+    ));
+    debug!("re-running workflow run {run_id} at {url}…");
+
+    match client
+        .send_body(Method::POST, &url, &serde_json::Map::new())
+        .await
+    {
+        Ok(_) => {
+            info!("re-ran workflow run {run_id}");
+            Ok(())
+        }
+        Err(err) => {
+            error!("failed to re-run workflow run at {url}: {err:#}");
+            Err(classify(&err))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{body_json, method, path, query_param},
+    };
+
+    use super::*;
+    use crate::{transactions::wait_for_workflow_run_with, workflow::client::test_client};
+
+    fn run(status: &str, conclusion: Option<&str>, run_attempt: u64) -> serde_json::Value {
+        let mut run: serde_json::Value =
+            serde_json::from_str(include_str!("../../tests/fixtures/workflow_run.json")).unwrap();
+        run["event"] = "workflow_dispatch".into();
+        run["status"] = status.into();
+        run["conclusion"] = conclusion.into();
+        run["run_attempt"] = run_attempt.into();
+        run
+    }
+
+    #[tokio::test]
+    async fn dispatches_and_waits() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(
+                "/repos/octo-org/octo-repo/actions/workflows/build.yml/dispatches",
+            ))
+            .and(body_json(
+                serde_json::json!({ "ref": "master", "inputs": { "target": "production" } }),
+            ))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/repos/octo-org/octo-repo/actions/workflows/build.yml/runs",
+            ))
+            .and(query_param("event", "workflow_dispatch"))
+            .and(query_param("branch", "master"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": 1,
+                "workflow_runs": [run("queued", None, 1)],
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo-org/octo-repo/actions/runs/30433642"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(run("in_progress", None, 1)))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo-org/octo-repo/actions/runs/30433642"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(run(
+                "completed",
+                Some("failure"),
+                1,
+            )))
+            .up_to_n_times(3)
+            .with_priority(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo-org/octo-repo/actions/runs/30433642"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(run(
+                "completed",
+                Some("success"),
+                2,
+            )))
+            .with_priority(3)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let interval = Duration::from_millis(10);
+        let dispatched_at = dispatch_workflow_with(
+            &client,
+            "octo-org",
+            "octo-repo",
+            "build.yml",
+            "master",
+            &[("target", "production")],
+        )
+        .await
+        .unwrap();
+        let run = find_dispatched_run_with(
+            &client,
+            "octo-org",
+            "octo-repo",
+            "build.yml",
+            "refs/heads/master",
+            dispatched_at,
+            interval,
+        )
+        .await
+        .unwrap();
+        assert_eq!(run.id, 30_433_642);
+
+        let run = wait_for_workflow_run_with(
+            &client,
+            "octo-org",
+            "octo-repo",
+            "30433642",
+            None,
+            interval,
+        )
+        .await
+        .unwrap();
+        assert!(!run.is_successful());
+        assert_eq!(run.run_attempt, Some(1));
+
+        // Waits for the new attempt, while the previous one still shows as completed
+        let run = wait_for_workflow_run_with(
+            &client,
+            "octo-org",
+            "octo-repo",
+            "30433642",
+            Some(1),
+            interval,
+        )
+        .await
+        .unwrap();
+        assert!(run.is_successful());
+        assert_eq!(run.run_attempt, Some(2));
+    }
+}
@@ -1,62 +1,211 @@
 use futures::Stream;
+use reqwest::StatusCode;
 use tokio_util::bytes::Bytes;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::{
     framework::{StateError, StateResult},
-    workflow::artifact::{Artifact, github_api_request_builder},
+    transactions::classify::classify,
+    workflow::{
+        artifact::{Artifact, EXPIRY_WARNING},
+        client::{GITHUB_CLIENT, GitHubClient},
+        error::error_for_status,
+    },
 };
 
-/// Downloads the specified artifact from GitHub.
+/// Downloads the specified artifact from GitHub using the shared [`GITHUB_CLIENT`].
 ///
 /// # Errors
 ///
 /// Returns an error that instructs retrying or cancelling if downloading the artifact fails.
+///
+/// See: [`download_artifact_with`]
 pub async fn download_artifact(
     artifact: &Artifact,
 ) -> StateResult<impl Stream<Item = Result<Bytes, reqwest::Error>> + use<>> {
+    download_artifact_with(&GITHUB_CLIENT, artifact).await
+}
+
+/// Downloads the specified artifact from GitHub using the given client.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if downloading the artifact fails, or cancelling without a request if the artifact has expired. The response status is checked before streaming, so that an error body is never read as the archive.
+///
+/// See: [`GitHubError`](crate::workflow::error::GitHubError)
+pub async fn download_artifact_with(
+    client: &GitHubClient,
+    artifact: &Artifact,
+) -> StateResult<impl Stream<Item = Result<Bytes, reqwest::Error>> + use<>> {
+    if artifact.is_expired() {
+        error!("refusing to download artifact {artifact}: artifact expired");
+        return Err(StateError::Cancelled);
+    }
+    if let Some(expires_in) = artifact.expires_in()
+        && expires_in < EXPIRY_WARNING
+    {
+        warn!(
+            "artifact {artifact} expires in {} minutes!",
+            expires_in.num_minutes()
+        );
+    }
+
     debug!(
         "requesting download from {}…",
         &artifact.archive_download_url
     );
 
-    match github_api_request_builder(&artifact.archive_download_url)
-        .send()
-        .await
-    {
-        Ok(resp) => {
-            let stream = resp.bytes_stream();
+    let request = match client.get(&artifact.archive_download_url).await {
+        Ok(request) => request,
+        Err(err) => {
+            error!("failed to authenticate download from {artifact}: {err:#}");
+            return Err(classify(&err));
+        }
+    };
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(err) => {
+            error!(
+                "failed to request download from {}: {err}",
+                &artifact.archive_download_url
+            );
+            return Err(classify(&err.into()));
+        }
+    };
+
+    match error_for_status(response).await {
+        Ok(response) => {
             info!("requested download from {}", artifact.archive_download_url);
-            Ok(stream)
+            Ok(response.bytes_stream())
+        }
+        Err(err) if err.status == StatusCode::GONE => {
+            error!("failed to request download of {artifact}: artifact expired or removed ({err})");
+            Err(StateError::Cancelled)
         }
-        Err(err) => match err.status() {
-            Some(reqwest::StatusCode::GONE) => {
-                error!("failed to request download: artifact expired or removed");
-                Err(StateError::Cancelled)
-            }
-            Some(status) => {
-                if let Some(reason) = status.canonical_reason() {
-                    error!(
-                        "failed to request download from {}: {} {reason}",
-                        &artifact.archive_download_url,
-                        status.as_u16()
-                    );
-                } else {
-                    error!(
-                        "failed to request download from {}: {}",
-                        &artifact.archive_download_url,
-                        status.as_u16()
-                    )
-                }
-                Err(StateError::Retry)
-            }
-            None => {
-                error!(
-                    "failed to download artifact at {}",
-                    &artifact.archive_download_url
-                );
-                Err(StateError::Retry)
-            }
-        },
+        Err(err) => {
+            error!("failed to request download of {artifact}: {err}");
+            Err(err.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{SecondsFormat, TimeDelta, Utc};
+    use futures::TryStreamExt as _;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path},
+    };
+
+    use super::*;
+    use crate::workflow::client::test_client;
+
+    #[tokio::test]
+    async fn refuses_expired_artifacts() {
+        let server = MockServer::start().await;
+        Mock::given(wiremock::matchers::any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let artifact = Artifact::new(
+            1,
+            "site",
+            client.url("/repos/octocat/hello/actions/artifacts/1"),
+        )
+        .with_created_at(Utc::now() - TimeDelta::days(90))
+        .with_expires_at(Utc::now() - TimeDelta::minutes(1));
+
+        assert_eq!(
+            download_artifact_with(&client, &artifact).await.err(),
+            Some(StateError::Cancelled)
+        );
+
+        // Marked as expired by GitHub before `expires_at`
+        let artifact = artifact_expiring_in(&client, TimeDelta::days(1), true);
+        assert_eq!(
+            download_artifact_with(&client, &artifact).await.err(),
+            Some(StateError::Cancelled)
+        );
+    }
+
+    #[tokio::test]
+    async fn downloads_artifacts_about_to_expire() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo-org/octo-docs/actions/artifacts/11/zip"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"PK".as_slice()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let artifact = artifact_expiring_in(&client, TimeDelta::minutes(10), false);
+        assert!(artifact.expires_in().unwrap() < EXPIRY_WARNING);
+
+        let stream = download_artifact_with(&client, &artifact).await.unwrap();
+        let chunks: Vec<Bytes> = stream.try_collect().await.unwrap();
+        assert_eq!(chunks.concat(), b"PK");
+    }
+
+    #[tokio::test]
+    async fn classifies_failed_downloads() {
+        let server = MockServer::start().await;
+        let client = test_client(&server);
+        for (status, expected) in [
+            (401, StateError::Cancelled),
+            (404, StateError::Cancelled),
+            (410, StateError::Cancelled),
+            (500, StateError::Retry),
+            (503, StateError::Retry),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!(
+                    "/repos/octocat/hello/actions/artifacts/{status}/zip"
+                )))
+                .respond_with(
+                    ResponseTemplate::new(status)
+                        .set_body_string(r#"{"message":"Something went wrong"}"#),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let artifact = Artifact::new(
+                u64::from(status),
+                "site",
+                client.url(&format!("/repos/octocat/hello/actions/artifacts/{status}")),
+            );
+            assert_eq!(
+                download_artifact_with(&client, &artifact).await.err(),
+                Some(expected),
+                "status {status}"
+            );
+        }
+    }
+
+    fn artifact_expiring_in(
+        client: &GitHubClient,
+        expires_in: TimeDelta,
+        expired: bool,
+    ) -> Artifact {
+        let expires_at = (Utc::now() + expires_in).to_rfc3339_opts(SecondsFormat::Secs, true);
+        serde_json::from_value(serde_json::json!({
+            "id": 11,
+            "node_id": "MDg6QXJ0aWZhY3QxMQ==",
+            "name": "Rails",
+            "size_in_bytes": 556,
+            "url": client.url("/repos/octo-org/octo-docs/actions/artifacts/11"),
+            "archive_download_url": client.url("/repos/octo-org/octo-docs/actions/artifacts/11/zip"),
+            "expired": expired,
+            "created_at": "2020-01-10T14:59:22Z",
+            "expires_at": expires_at,
+            "updated_at": "2020-01-10T14:59:22Z"
+        }))
+        .unwrap()
     }
 }
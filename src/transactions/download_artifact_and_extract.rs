@@ -1,9 +1,19 @@
-use std::{fmt::Debug, path::Path};
+use std::{
+    fmt::{Debug, Display},
+    io,
+    path::Path,
+};
 
 use crate::{
-    framework::StateResult,
-    transactions::{download_artifact, extract_archive},
-    workflow::artifact::Artifact,
+    framework::{StateError, StateResult},
+    transactions::{
+        delete_artifact_with, download_artifact_with, enforce_policy_with, extract_archive,
+    },
+    workflow::{
+        artifact::Artifact,
+        client::{GITHUB_CLIENT, GitHubClient},
+        policy::DeploymentPolicy,
+    },
 };
 
 use anyhow::{Error, anyhow};
@@ -21,25 +31,103 @@ enum Case {
     HashUnmatch,
 }
 
-/// Downloads an [`Artifact`] and extracts the downloaded archive to a specified path.
+/// Options of [`download_artifact_and_extract_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct DownloadOptions {
+    policy: Option<DeploymentPolicy>,
+    delete_after_success: bool,
+}
+
+impl DownloadOptions {
+    /// Creates [`DownloadOptions`] that download any artifact.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Evaluates a [`DeploymentPolicy`] against the workflow run of the artifact before downloading.
+    ///
+    /// See: [`enforce_policy_with`]
+    pub fn policy(mut self, policy: DeploymentPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Deletes the artifact from GitHub once extracted successfully, freeing its storage. Failing to delete is logged without failing the download.
+    ///
+    /// See: [`delete_artifact_with`]
+    pub const fn delete_after_success(mut self, delete_after_success: bool) -> Self {
+        self.delete_after_success = delete_after_success;
+        self
+    }
+}
+
+/// Downloads an [`Artifact`] using the shared [`GITHUB_CLIENT`] and extracts the downloaded archive to a specified path.
 ///
 /// # Errors
 ///
 /// Returns an error that instructs retrying or cancelling if downloading or extracting the artifact fails.
 ///
-/// See: [`download_artifact`], [`extract_archive`]
+/// See: [`download_artifact_and_extract_with`]
 pub async fn download_artifact_and_extract<P>(artifact: Artifact, path: P) -> StateResult<()>
 where
     P: AsRef<Path> + Send + Sync + Debug,
 {
-    match download_artifact(&artifact).await {
-        Ok(stream) => {
-            info!("downloading artifact {artifact}…",);
-            let case = extract(stream, artifact.digest.as_deref(), &path).await;
+    download_artifact_and_extract_with(&GITHUB_CLIENT, artifact, path).await
+}
+
+/// Downloads an [`Artifact`] using the given client and extracts the downloaded archive to a specified path.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if downloading or extracting the artifact fails.
+///
+/// See: [`download_artifact_with`], [`extract_archive`]
+pub async fn download_artifact_and_extract_with<P>(
+    client: &GitHubClient,
+    artifact: Artifact,
+    path: P,
+) -> StateResult<()>
+where
+    P: AsRef<Path> + Send + Sync + Debug,
+{
+    download_artifact_and_extract_with_options(client, artifact, path, &DownloadOptions::new())
+        .await
+}
+
+/// Downloads an [`Artifact`] using the given client and options, and extracts the downloaded archive to a specified path.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if downloading or extracting the artifact fails, or cancelling if the artifact violates the deployment policy or does not match its digest. The path is removed if extracting or verifying fails.
+///
+/// See: [`DownloadOptions`]
+pub async fn download_artifact_and_extract_with_options<P>(
+    client: &GitHubClient,
+    artifact: Artifact,
+    path: P,
+    options: &DownloadOptions,
+) -> StateResult<()>
+where
+    P: AsRef<Path> + Send + Sync + Debug,
+{
+    if let Some(policy) = &options.policy {
+        enforce_policy_with(client, &artifact, policy).await?;
+    }
 
-            info!("downloaded artifact {artifact}");
-            cleanup(artifact.clone(), case, &path).await;
+    match download_artifact_with(client, &artifact).await {
+        Ok(stream) => {
+            extract_verified(
+                &artifact,
+                stream.map_err(io::Error::other),
+                expected_digest(&artifact),
+                &path,
+            )
+            .await?;
 
+            if options.delete_after_success {
+                // Already logged
+                let _ = delete_artifact_with(client, &artifact).await;
+            }
             Ok(())
         }
         Err(err) => {
@@ -49,9 +137,48 @@ where
     }
 }
 
+/// Gets the hex-encoded SHA-256 digest of a GitHub artifact, stripped of its `sha256:` prefix.
+pub(crate) fn expected_digest(artifact: &Artifact) -> Option<&str> {
+    let digest = artifact.digest.as_deref()?;
+    let hex = digest.strip_prefix("sha256:");
+    if hex.is_none() {
+        warn!("ignoring unsupported digest {digest} of {artifact}");
+    }
+    hex
+}
+
+/// Extracts a downloaded archive to a specified path, verifying it against the hex-encoded SHA-256 digest if provided. The path is removed if extracting or verifying fails.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying if extracting the archive fails, or cancelling if the archive does not match the digest.
+pub(crate) async fn extract_verified<A, S, P>(
+    artifact: &A,
+    stream: S,
+    digest: Option<&str>,
+    path: P,
+) -> StateResult<()>
+where
+    A: Display + Sync,
+    S: Stream<Item = io::Result<Bytes>> + Unpin,
+    P: AsRef<Path> + Send + Sync + Debug,
+{
+    info!("downloading artifact {artifact}…");
+    let case = extract(stream, digest, &path).await;
+
+    info!("downloaded artifact {artifact}");
+    let result = match case {
+        Case::Extracted => Ok(()),
+        Case::Failed(_) => Err(StateError::Retry),
+        Case::HashUnmatch => Err(StateError::Cancelled),
+    };
+    cleanup(artifact, case, &path).await;
+    result
+}
+
 async fn extract<S, P>(stream: S, digest: Option<&str>, path: P) -> Case
 where
-    S: Stream<Item = Result<Bytes, reqwest::Error>> + Unpin,
+    S: Stream<Item = io::Result<Bytes>> + Unpin,
     P: AsRef<Path> + Send + Sync + Debug,
 {
     let mut sha_hasher = sha2::Sha256::new();
@@ -60,7 +187,6 @@ where
             sha_hasher.update(&bytes);
             bytes
         })
-        .map_err(std::io::Error::other)
         .into_async_read();
 
     match extract_archive(ZipFileReader::new(&mut read), &path).await {
@@ -69,7 +195,7 @@ where
             drop(read.read_to_end(&mut Vec::new()).await);
 
             if let Some(digest) = digest {
-                if hex::encode(sha_hasher.finalize()) == digest[7..] {
+                if hex::encode(sha_hasher.finalize()).eq_ignore_ascii_case(digest) {
                     Case::Extracted
                 } else {
                     Case::HashUnmatch
@@ -83,8 +209,9 @@ where
     }
 }
 
-async fn cleanup<P>(artifact: Artifact, case: Case, path: P)
+async fn cleanup<A, P>(artifact: &A, case: Case, path: P)
 where
+    A: Display + Sync,
     P: AsRef<Path> + Send + Sync + Debug,
 {
     match case {
@@ -99,3 +226,59 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write as _};
+
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path},
+    };
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::*;
+    use crate::workflow::client::test_client;
+
+    #[tokio::test]
+    async fn fails_on_digest_mismatch() {
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+        archive
+            .start_file("index.html", SimpleFileOptions::default())
+            .unwrap();
+        archive.write_all(b"tampered").unwrap();
+        let archive = archive.finish().unwrap().into_inner();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello/actions/artifacts/1/zip"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let artifact = Artifact::new(
+            1,
+            "site",
+            client.url("/repos/octocat/hello/actions/artifacts/1"),
+        )
+        .with_digest(format!("sha256:{}", "0".repeat(64)));
+
+        let dir = std::env::temp_dir().join(format!(
+            "api-framework-digest-mismatch-{}",
+            std::process::id()
+        ));
+        let options = DownloadOptions::new().delete_after_success(true);
+        assert_eq!(
+            download_artifact_and_extract_with_options(&client, artifact, &dir, &options).await,
+            Err(StateError::Cancelled)
+        );
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,362 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use async_zip::base::read::stream::ZipFileReader;
+use futures::TryStreamExt as _;
+use parking_lot::Mutex;
+use tracing::{debug, error, info};
+
+use crate::{
+    framework::{Lifecycle, StateError, StateResult},
+    transactions::{classify::classify, extract_archive},
+    workflow::{
+        client::{GITHUB_CLIENT, GitHubClient},
+        error::error_for_status,
+    },
+};
+
+/// The logs of a workflow run, extracted to a directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunLogs {
+    /// The directory the logs are extracted to.
+    pub dir: PathBuf,
+    /// The logs of each job, ordered by name.
+    pub jobs: Vec<JobLogs>,
+}
+
+/// The logs of a job in a workflow run.
+///
+/// GitHub archives the whole log of each job as `{index}_{job}.txt`, and the log of each step as `{job}/{index}_{step}.txt`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobLogs {
+    /// The name of the job.
+    pub name: String,
+    /// The whole log of the job, if archived.
+    pub log: Option<PathBuf>,
+    /// The log of each step, ordered by their index.
+    pub steps: Vec<PathBuf>,
+}
+
+impl RunLogs {
+    /// Indexes the logs extracted to a directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory fails to read.
+    pub async fn from_dir<P>(dir: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref().to_owned();
+        let mut jobs: Vec<JobLogs> = Vec::new();
+        fn job<'a>(jobs: &'a mut Vec<JobLogs>, name: &str) -> &'a mut JobLogs {
+            let index = match jobs.iter().position(|job| job.name == name) {
+                Some(index) => index,
+                None => {
+                    jobs.push(JobLogs {
+                        name: name.to_owned(),
+                        log: None,
+                        steps: Vec::new(),
+                    });
+                    jobs.len() - 1
+                }
+            };
+            &mut jobs[index]
+        }
+
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if entry.file_type().await?.is_dir() {
+                let mut steps = Vec::new();
+                let mut step_entries = tokio::fs::read_dir(&path).await?;
+                while let Some(step) = step_entries.next_entry().await? {
+                    steps.push(step.path());
+                }
+                // Orders `10_` after `9_`, and files without an index last
+                steps.sort_by_cached_key(|step| {
+                    let file_name = step.file_name().unwrap_or_default().to_string_lossy();
+                    let index = split_index(&file_name).map_or(u64::MAX, |(index, _)| index);
+                    (index, step.clone())
+                });
+                job(&mut jobs, &file_name).steps = steps;
+            } else if let Some(stem) = file_name.strip_suffix(".txt") {
+                let name = split_index(stem).map_or(stem, |(_, name)| name);
+                job(&mut jobs, name).log = Some(path);
+            }
+        }
+        jobs.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self { dir, jobs })
+    }
+
+    /// Gets the logs of a job by its name.
+    pub fn job(&self, name: &str) -> Option<&JobLogs> {
+        self.jobs.iter().find(|job| job.name == name)
+    }
+}
+
+/// Splits the index prefix like `0_` from a file name in the log archive.
+fn split_index(file_name: &str) -> Option<(u64, &str)> {
+    let (index, name) = file_name.split_once('_')?;
+    Some((index.parse().ok()?, name))
+}
+
+/// Downloads the log archive of a workflow run from GitHub using the shared [`GITHUB_CLIENT`], and extracts it to a specified path.
+///
+/// The path is replaced if existing.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if downloading or extracting the logs fails.
+///
+/// See: [`download_run_logs_with`]
+pub async fn download_run_logs<P>(
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+    path: P,
+) -> StateResult<RunLogs>
+where
+    P: AsRef<Path> + Send + Sync,
+{
+    download_run_logs_with(&GITHUB_CLIENT, owner, repo, run_id, path).await
+}
+
+/// Downloads the log archive of a workflow run from GitHub using the given client, and extracts it to a specified path.
+///
+/// The path is replaced if existing.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if downloading or extracting the logs fails. Logs are kept by GitHub for a limited time, after which downloading is cancelled.
+///
+/// See: [`extract_archive`]
+pub async fn download_run_logs_with<P>(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+    path: P,
+) -> StateResult<RunLogs>
+where
+    P: AsRef<Path> + Send + Sync,
+{
+    let url = client.url(&format!("/repos/{owner}/{repo}/actions/runs/{run_id}/logs"));
+    debug!("downloading logs from {url}…");
+
+    let response = async {
+        let response = client.get(&url).await?.send().await?;
+        anyhow::Ok(error_for_status(response).await?)
+    };
+    let response = match response.await {
+        Ok(response) => response,
+        Err(err) => {
+            error!("failed to download logs from {url}: {err:#}");
+            return Err(classify(&err));
+        }
+    };
+
+    let mut read = response
+        .bytes_stream()
+        .map_err(io::Error::other)
+        .into_async_read();
+    if let Err(err) = extract_archive(ZipFileReader::new(&mut read), &path).await {
+        error!(
+            "failed to extract logs from {url} to {}: {err}",
+            path.as_ref().display()
+        );
+        drop(tokio::fs::remove_dir_all(&path).await);
+        return Err(StateError::Retry);
+    }
+
+    match RunLogs::from_dir(&path).await {
+        Ok(logs) => {
+            info!(
+                "downloaded logs of {} jobs from {url} to {}",
+                logs.jobs.len(),
+                logs.dir.display()
+            );
+            Ok(logs)
+        }
+        Err(err) => {
+            error!("failed to index logs at {}: {err}", path.as_ref().display());
+            Err(StateError::Retry)
+        }
+    }
+}
+
+/// Attaches the logs of a workflow run to a failed deployment, by downloading them once the business fails after retrying.
+///
+/// Cancelled businesses, like superseded or refused deployments, are not investigated, so their logs are not downloaded.
+///
+/// The logs are available through [`Self::logs`] afterwards. Failing to download is logged without affecting the deployment.
+///
+/// See: [`QueuedAsyncFramework::run_with_lifecycle`](crate::framework::queued_async::QueuedAsyncFramework::run_with_lifecycle)
+#[derive(Debug)]
+pub struct RunLogsOnFailure {
+    client: GitHubClient,
+    owner: String,
+    repo: String,
+    run_id: String,
+    dir: PathBuf,
+    logs: Mutex<Option<RunLogs>>,
+}
+
+impl RunLogsOnFailure {
+    /// Creates a [`RunLogsOnFailure`] extracting the logs of a workflow run to `dir` on failure after retrying.
+    pub fn new<P>(client: GitHubClient, owner: &str, repo: &str, run_id: &str, dir: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            client,
+            owner: owner.to_owned(),
+            repo: repo.to_owned(),
+            run_id: run_id.to_owned(),
+            dir: dir.into(),
+            logs: Mutex::new(None),
+        }
+    }
+
+    /// Gets the logs, once downloaded on failure.
+    pub fn logs(&self) -> Option<RunLogs> {
+        self.logs.lock().clone()
+    }
+}
+
+impl Lifecycle for RunLogsOnFailure {
+    async fn finished(&self, name: &str, result: Result<(), StateError>) {
+        if result != Err(StateError::Retry) {
+            return;
+        }
+
+        if let Ok(logs) = download_run_logs_with(
+            &self.client,
+            &self.owner,
+            &self.repo,
+            &self.run_id,
+            &self.dir,
+        )
+        .await
+        {
+            error!(
+                "{name} failed, logs of workflow run {} are at {}",
+                self.run_id,
+                logs.dir.display()
+            );
+            *self.logs.lock() = Some(logs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write as _};
+
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path},
+    };
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::*;
+    use crate::workflow::client::test_client;
+
+    #[tokio::test]
+    async fn downloads_logs_per_job() {
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in [
+            ("0_build.txt", "building"),
+            ("build/1_Set up job.txt", "setting up"),
+            ("build/2_Run make.txt", "making"),
+            ("build/10_Complete job.txt", "completing"),
+            ("1_deploy.txt", "deploying"),
+        ] {
+            archive
+                .start_file(name, SimpleFileOptions::default())
+                .unwrap();
+            archive.write_all(content.as_bytes()).unwrap();
+        }
+        let archive = archive.finish().unwrap().into_inner();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello/actions/runs/30433642/logs"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let dir = std::env::temp_dir().join(format!("api-framework-logs-{}", std::process::id()));
+        let logs = download_run_logs_with(&client, "octocat", "hello", "30433642", &dir)
+            .await
+            .unwrap();
+
+        let names: Vec<_> = logs.jobs.iter().map(|job| job.name.as_str()).collect();
+        assert_eq!(names, ["build", "deploy"]);
+        let build = logs.job("build").unwrap();
+        assert_eq!(
+            tokio::fs::read_to_string(build.log.as_ref().unwrap())
+                .await
+                .unwrap(),
+            "building"
+        );
+        let steps: Vec<_> = build
+            .steps
+            .iter()
+            .map(|step| step.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            steps,
+            ["1_Set up job.txt", "2_Run make.txt", "10_Complete job.txt"]
+        );
+        assert!(logs.job("deploy").unwrap().steps.is_empty());
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn downloads_logs_once_failed_after_retrying() {
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+        archive
+            .start_file("0_build.txt", SimpleFileOptions::default())
+            .unwrap();
+        archive.write_all(b"building").unwrap();
+        let archive = archive.finish().unwrap().into_inner();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello/actions/runs/30433642/logs"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let dir = std::env::temp_dir().join(format!(
+            "api-framework-logs-on-failure-{}",
+            std::process::id()
+        ));
+        let reporter = RunLogsOnFailure::new(client, "octocat", "hello", "30433642", &dir);
+
+        reporter.finished("deployment", Ok(())).await;
+        reporter
+            .finished("deployment", Err(StateError::Cancelled))
+            .await;
+        assert_eq!(reporter.logs(), None);
+
+        reporter
+            .finished("deployment", Err(StateError::Retry))
+            .await;
+        let logs = reporter.logs().unwrap();
+        assert_eq!(logs.jobs.len(), 1);
+        assert!(logs.job("build").is_some());
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
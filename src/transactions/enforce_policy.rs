@@ -0,0 +1,144 @@
+use tracing::{debug, error, info};
+
+use crate::{
+    framework::{StateError, StateResult},
+    transactions::classify::classify,
+    workflow::{
+        WorkflowRun,
+        artifact::Artifact,
+        client::{GITHUB_CLIENT, GitHubClient},
+        policy::{DeploymentPolicy, PolicyViolation},
+    },
+};
+
+/// Evaluates a [`DeploymentPolicy`] against the workflow run of an [`Artifact`] using the shared [`GITHUB_CLIENT`], fetching the full workflow run if the embedded one lacks the required fields.
+///
+/// # Errors
+///
+/// Returns an error that instructs cancelling if the policy is violated, or retrying or cancelling if fetching the workflow run fails.
+///
+/// See: [`enforce_policy_with`]
+pub async fn enforce_policy(
+    artifact: &Artifact,
+    policy: &DeploymentPolicy,
+) -> StateResult<WorkflowRun> {
+    enforce_policy_with(&GITHUB_CLIENT, artifact, policy).await
+}
+
+/// Evaluates a [`DeploymentPolicy`] against the workflow run of an [`Artifact`] using the given client, fetching the full workflow run if the embedded one lacks the required fields.
+///
+/// # Errors
+///
+/// Returns an error that instructs cancelling if the policy is violated, or retrying or cancelling if fetching the workflow run fails.
+pub async fn enforce_policy_with(
+    client: &GitHubClient,
+    artifact: &Artifact,
+    policy: &DeploymentPolicy,
+) -> StateResult<WorkflowRun> {
+    let Some(run) = &artifact.workflow_run else {
+        return violated(artifact, &PolicyViolation::MissingWorkflowRun);
+    };
+
+    let run = if policy.needs_full_run(run) {
+        // Artifacts are at `/repos/{owner}/{repo}/actions/artifacts/{id}`, next to the runs
+        let Some((actions, _)) = artifact.url.rsplit_once("/artifacts/") else {
+            error!("invalid artifact URL {}!", artifact.url);
+            return Err(StateError::Cancelled);
+        };
+        let url = format!("{actions}/runs/{}", run.id);
+        debug!("fetching workflow run {run} of {artifact} to evaluate the deployment policy…");
+
+        match client.get_json::<WorkflowRun>(&url).await {
+            Ok(run) => run,
+            Err(err) => {
+                error!("failed to fetch workflow run from {url}: {err:#}");
+                return Err(classify(&err));
+            }
+        }
+    } else {
+        run.clone()
+    };
+
+    match policy.evaluate(&run) {
+        Ok(()) => {
+            info!("workflow run {run} of {artifact} conforms to the deployment policy");
+            Ok(run)
+        }
+        Err(violation) => violated(artifact, &violation),
+    }
+}
+
+fn violated<T>(artifact: &Artifact, violation: &PolicyViolation) -> StateResult<T> {
+    error!("refusing to deploy {artifact}: policy violation: {violation}");
+    Err(StateError::Cancelled)
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path},
+    };
+
+    use super::*;
+    use crate::workflow::client::test_builder;
+
+    #[tokio::test]
+    async fn fetches_full_runs_to_evaluate() {
+        let server = MockServer::start().await;
+        let mut full_run: serde_json::Value =
+            serde_json::from_str(include_str!("../../tests/fixtures/workflow_run.json")).unwrap();
+        full_run["status"] = "completed".into();
+        full_run["conclusion"] = "failure".into();
+        full_run["head_repository"] = full_run["repository"].clone();
+        Mock::given(method("GET"))
+            .and(path("/repos/octo-org/octo-repo/actions/runs/30433642"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(full_run))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_builder(&server).no_cache().build().unwrap();
+        let artifact = |head_repository_id: u64| -> Artifact {
+            serde_json::from_value(serde_json::json!({
+                "id": 11,
+                "node_id": "MDg6QXJ0aWZhY3QxMQ==",
+                "name": "site",
+                "size_in_bytes": 556,
+                "url": client.url("/repos/octo-org/octo-repo/actions/artifacts/11"),
+                "archive_download_url": client.url("/repos/octo-org/octo-repo/actions/artifacts/11/zip"),
+                "expired": false,
+                "workflow_run": {
+                    "id": 30433642,
+                    "repository_id": 1296269,
+                    "head_repository_id": head_repository_id,
+                    "head_branch": "master",
+                    "head_sha": "acb5820ced9479c074f688cc328bf03f341a511d"
+                }
+            }))
+            .unwrap()
+        };
+
+        // Denied without fetching
+        let policy = DeploymentPolicy::new().branch("release").conclusion(None);
+        assert_eq!(
+            enforce_policy_with(&client, &artifact(1_296_269), &policy).await,
+            Err(StateError::Cancelled)
+        );
+        assert_eq!(
+            enforce_policy_with(
+                &client,
+                &artifact(217_723_378),
+                &DeploymentPolicy::new().conclusion(None)
+            )
+            .await,
+            Err(StateError::Cancelled)
+        );
+
+        // Denied after fetching the conclusion
+        assert_eq!(
+            enforce_policy_with(&client, &artifact(1_296_269), &DeploymentPolicy::new()).await,
+            Err(StateError::Cancelled)
+        );
+    }
+}
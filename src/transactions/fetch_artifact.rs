@@ -1,12 +1,35 @@
-use crate::{framework::StateResult, transactions::fetch_artifacts, workflow::artifact::Artifact};
+use crate::{
+    framework::StateResult,
+    transactions::fetch_artifacts_with,
+    workflow::{
+        artifact::Artifact,
+        client::{GITHUB_CLIENT, GitHubClient},
+    },
+};
 
-/// Fetches the only artifact from GitHub using the given parameters.
+/// Fetches the only artifact from GitHub using the given parameters and the shared [`GITHUB_CLIENT`].
 ///
 /// # Errors
 ///
 /// Returns an error that instructs retrying or cancelling if fetching the artifact fails, or the number of fetched artifacts is not exactly one.
+///
+/// See: [`fetch_artifact_with`]
 pub async fn fetch_artifact(owner: &str, repo: &str, run_id: &str) -> StateResult<Artifact> {
-    fetch_artifacts(owner, repo, run_id, Some(1))
+    fetch_artifact_with(&GITHUB_CLIENT, owner, repo, run_id).await
+}
+
+/// Fetches the only artifact from GitHub using the given client and parameters.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the artifact fails, or the number of fetched artifacts is not exactly one.
+pub async fn fetch_artifact_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+) -> StateResult<Artifact> {
+    fetch_artifacts_with(client, owner, repo, run_id, Some(1))
         .await
         .map(|artifacts| artifacts[0].clone())
 }
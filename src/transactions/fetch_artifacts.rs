@@ -1,80 +1,193 @@
-use std::error::Error as _;
+use futures::{Stream, TryStreamExt as _};
 use tracing::{debug, error, info};
 
 use crate::{
     framework::{StateError, StateResult},
-    workflow::artifact::{Artifact, Artifacts, github_api_request_builder},
+    transactions::classify::classify,
+    workflow::{
+        artifact::{Artifact, Artifacts},
+        client::{GITHUB_CLIENT, GitHubClient},
+    },
 };
 
-/// Fetches artifacts from GitHub using the given parameters.
+/// Fetches artifacts from GitHub using the given parameters and the shared [`GITHUB_CLIENT`].
 ///
 /// # Errors
 ///
 /// Returns an error that instructs retrying or cancelling if fetching the artifacts fails, or the number of fetched artifacts does not match the expected count.
+///
+/// See: [`fetch_artifacts_with`]
 pub async fn fetch_artifacts(
     owner: &str,
     repo: &str,
     run_id: &str,
-    count: Option<u8>,
+    count: Option<u64>,
 ) -> StateResult<Vec<Artifact>> {
-    let url =
-        format!("https://api.github.com/repos/{owner}/{repo}/actions/runs/{run_id}/artifacts");
+    fetch_artifacts_with(&GITHUB_CLIENT, owner, repo, run_id, count).await
+}
+
+/// Fetches artifacts from GitHub using the given client and parameters, across all pages.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the artifacts fails, or the number of fetched artifacts does not match the expected count.
+///
+/// See: [`stream_artifacts`]
+pub async fn fetch_artifacts_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+    count: Option<u64>,
+) -> StateResult<Vec<Artifact>> {
+    let url = artifacts_url(client, owner, repo, run_id);
     match &count {
         Some(1) => debug!("fetching 1 artifact from {url}…"),
         Some(count) => debug!("fetching {count} artifacts from {url}…"),
         None => debug!("fetching artifacts from {url}…"),
     }
 
-    let response = match github_api_request_builder(&url).send().await {
-        Ok(response) => response,
-        Err(err) => {
-            error!("failed to fetch artifacts from {url}: {err}");
-            return match err {
-                _ if err.is_connect() || err.is_timeout() => Err(StateError::Retry),
-                _ => Err(StateError::Cancelled),
-            };
-        }
-    };
+    let artifacts: Vec<Artifact> = stream_artifacts(client, owner, repo, run_id)
+        .try_collect()
+        .await?;
 
-    match response.json::<Artifacts>().await {
-        Ok(artifacts) => match artifacts.total_count {
-            0 => {
-                error!("invalid workflow data: no artifacts at {url}!");
-                Err(StateError::Cancelled)
-            }
-            total_count => match &count {
-                Some(count) => match total_count {
-                    total_count if total_count < *count => {
-                        error!(
-                            "invalid workflow data: too little artifacts at {url}! expected {count}, got {total_count}"
-                        );
-                        Err(StateError::Cancelled)
-                    }
-                    total_count if total_count > *count => {
-                        error!(
-                            "invalid workflow data: too many artifacts at {url}! expected {count}, got {total_count}",
-                        );
-                        Err(StateError::Cancelled)
-                    }
-                    total_count => {
-                        match total_count {
-                            1 => info!("fetched 1 artifact from {url}"),
-                            count => info!("fetched {count} artifacts from {url}"),
-                        }
-                        Ok(artifacts.artifacts)
+    match artifacts.len() as u64 {
+        0 => {
+            error!("invalid workflow data: no artifacts at {url}!");
+            Err(StateError::Cancelled)
+        }
+        total_count => match &count {
+            Some(count) => match total_count {
+                total_count if total_count < *count => {
+                    error!(
+                        "invalid workflow data: too little artifacts at {url}! expected {count}, got {total_count}"
+                    );
+                    Err(StateError::Cancelled)
+                }
+                total_count if total_count > *count => {
+                    error!(
+                        "invalid workflow data: too many artifacts at {url}! expected {count}, got {total_count}",
+                    );
+                    Err(StateError::Cancelled)
+                }
+                total_count => {
+                    match total_count {
+                        1 => info!("fetched 1 artifact from {url}"),
+                        count => info!("fetched {count} artifacts from {url}"),
                     }
-                },
-                None => Ok(artifacts.artifacts),
+                    Ok(artifacts)
+                }
             },
+            None => Ok(artifacts),
         },
-        Err(err) => {
-            error!("failed to parse data from {url}: {err}");
+    }
+}
 
-            if let Some(source) = err.source() {
-                error!("{source}")
-            }
+/// Streams artifacts of a workflow run from GitHub using the given client, following the pagination until the last page.
+///
+/// # Errors
+///
+/// Yields an error that instructs retrying or cancelling if fetching a page fails, after which the stream ends.
+pub fn stream_artifacts<'a>(
+    client: &'a GitHubClient,
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+) -> impl Stream<Item = StateResult<Artifact>> + Send + 'a {
+    stream_artifacts_from(client, artifacts_url(client, owner, repo, run_id))
+}
 
-            Err(StateError::Retry)
-        }
+/// Streams artifacts from a list endpoint at `url`, following the pagination until the last page.
+pub(crate) fn stream_artifacts_from(
+    client: &GitHubClient,
+    url: String,
+) -> impl Stream<Item = StateResult<Artifact>> + Send + '_ {
+    client.paginate::<Artifacts>(&url).map_err(move |err| {
+        error!("failed to fetch artifacts from {url}: {err:#}");
+        classify(&err)
+    })
+}
+
+pub(crate) fn artifacts_url(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+) -> String {
+    client.url(&format!(
+        "/repos/{owner}/{repo}/actions/runs/{run_id}/artifacts"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{header, method, path, query_param},
+    };
+
+    use super::*;
+    use crate::{env::Secret, workflow::auth::GitHubAuth};
+
+    fn page(id: u64) -> String {
+        format!(
+            r#"{{
+                "total_count": 2,
+                "artifacts": [{{
+                    "id": {id},
+                    "node_id": "MDg6QXJ0aWZhY3Qx",
+                    "name": "site-{id}",
+                    "size_in_bytes": 1024,
+                    "url": "https://github.example.com/api/v3/repos/octocat/hello/actions/artifacts/{id}",
+                    "archive_download_url": "https://github.example.com/api/v3/repos/octocat/hello/actions/artifacts/{id}/zip",
+                    "expired": false
+                }}]
+            }}"#
+        )
+    }
+
+    #[tokio::test]
+    async fn fetches_all_pages_from_base_url() {
+        let server = MockServer::start().await;
+        let url = format!(
+            "{}/api/v3/repos/octocat/hello/actions/runs/42/artifacts",
+            server.uri()
+        );
+        Mock::given(method("GET"))
+            .and(path(
+                "/api/v3/repos/octocat/hello/actions/runs/42/artifacts",
+            ))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(page(2)))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/api/v3/repos/octocat/hello/actions/runs/42/artifacts",
+            ))
+            .and(query_param("per_page", "100"))
+            .and(header("authorization", "Bearer ghp_token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header(
+                        "link",
+                        format!(r#"<{url}?per_page=100&page=2>; rel="next""#),
+                    )
+                    .set_body_string(page(1)),
+            )
+            .mount(&server)
+            .await;
+
+        let client =
+            GitHubClient::builder(GitHubAuth::Token(Secret::new(String::from("ghp_token"))))
+                .enterprise_url(&server.uri())
+                .build()
+                .unwrap();
+        let artifacts = fetch_artifacts_with(&client, "octocat", "hello", "42", Some(2))
+            .await
+            .unwrap();
+
+        assert_eq!(artifacts[0].name, "site-1");
+        assert_eq!(artifacts[1].name, "site-2");
     }
 }
@@ -0,0 +1,232 @@
+use futures::TryStreamExt as _;
+use reqwest::Url;
+use tracing::{debug, error, info};
+
+use crate::{
+    framework::{StateError, StateResult},
+    transactions::{ArtifactMetadata, fetch_artifacts::stream_artifacts_from},
+    workflow::{
+        artifact::Artifact,
+        client::{GITHUB_CLIENT, GitHubClient},
+    },
+};
+
+/// The filters to find the latest artifact across a repository.
+///
+/// # Examples
+///
+/// ```rust
+/// # use api_framework::transactions::LatestArtifactQuery;
+/// // the newest non-expired `site` artifact built from `main`
+/// let query = LatestArtifactQuery::new("site").branch("main");
+/// ```
+#[derive(Debug, Clone)]
+pub struct LatestArtifactQuery {
+    name: String,
+    branch: Option<String>,
+    head_sha: Option<String>,
+    include_expired: bool,
+}
+
+impl LatestArtifactQuery {
+    /// Creates a [`LatestArtifactQuery`] matching artifacts with the exact name.
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            branch: None,
+            head_sha: None,
+            include_expired: false,
+        }
+    }
+
+    /// Only matches artifacts built from the branch.
+    pub fn branch<S>(mut self, branch: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.branch = Some(branch.into());
+        self
+    }
+
+    /// Only matches artifacts built from the commit.
+    pub fn head_sha<S>(mut self, head_sha: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.head_sha = Some(head_sha.into());
+        self
+    }
+
+    /// Also matches expired artifacts, which cannot be downloaded anymore.
+    pub const fn include_expired(mut self, include_expired: bool) -> Self {
+        self.include_expired = include_expired;
+        self
+    }
+
+    /// Checks if an artifact matches the filters.
+    pub fn matches(&self, artifact: &Artifact) -> bool {
+        self.matches_metadata(&artifact.into())
+    }
+
+    /// Checks if the metadata of an artifact from any [`ArtifactSource`](crate::transactions::ArtifactSource) matches the filters.
+    pub fn matches_metadata(&self, metadata: &ArtifactMetadata) -> bool {
+        metadata.name == self.name
+            && (self.include_expired || !metadata.is_expired())
+            && self
+                .branch
+                .as_ref()
+                .is_none_or(|branch| metadata.head_branch.as_ref() == Some(branch))
+            && self
+                .head_sha
+                .as_ref()
+                .is_none_or(|head_sha| metadata.head_sha.as_ref() == Some(head_sha))
+    }
+}
+
+/// Finds the most recent artifact across a repository that matches the query, using the shared [`GITHUB_CLIENT`].
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the artifacts fails, or cancelling if no artifact matches.
+///
+/// See: [`find_latest_artifact_with`]
+pub async fn find_latest_artifact(
+    owner: &str,
+    repo: &str,
+    query: &LatestArtifactQuery,
+) -> StateResult<Artifact> {
+    find_latest_artifact_with(&GITHUB_CLIENT, owner, repo, query).await
+}
+
+/// Finds the most recent artifact across a repository that matches the query, using the given client.
+///
+/// Only the pages up to the first match are fetched.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the artifacts fails, or cancelling if no artifact matches.
+pub async fn find_latest_artifact_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    query: &LatestArtifactQuery,
+) -> StateResult<Artifact> {
+    let mut url = client.url(&format!("/repos/{owner}/{repo}/actions/artifacts"));
+    if let Ok(mut parsed) = Url::parse(&url) {
+        parsed.query_pairs_mut().append_pair("name", &query.name);
+        url = parsed.into();
+    }
+    debug!("finding the latest artifact matching {query:?} from {url}…");
+
+    // The artifacts are listed newest first, so the pagination stops at the first match
+    let artifacts = stream_artifacts_from(client, url.clone())
+        .try_filter(|artifact| futures::future::ready(query.matches(artifact)));
+    futures::pin_mut!(artifacts);
+    let latest = artifacts.try_next().await?;
+
+    match latest {
+        Some(artifact) => {
+            info!("found the latest artifact {artifact} from {url}");
+            Ok(artifact)
+        }
+        None => {
+            error!("no artifacts at {url} match {query:?}!");
+            Err(StateError::Cancelled)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path, query_param},
+    };
+
+    use super::*;
+    use crate::workflow::client::test_client;
+
+    fn artifact(id: u64, head_branch: &str, head_sha: &str, expired: bool) -> String {
+        format!(
+            r#"{{
+                "id": {id},
+                "node_id": "MDg6QXJ0aWZhY3Qx",
+                "name": "site",
+                "size_in_bytes": 556,
+                "url": "https://api.github.com/repos/octo-org/octo-docs/actions/artifacts/{id}",
+                "archive_download_url": "https://api.github.com/repos/octo-org/octo-docs/actions/artifacts/{id}/zip",
+                "expired": {expired},
+                "created_at": "2020-01-10T14:59:22Z",
+                "expires_at": "2099-03-21T14:59:22Z",
+                "updated_at": "2020-02-21T14:59:22Z",
+                "workflow_run": {{
+                    "id": 2332938,
+                    "repository_id": 1296269,
+                    "head_repository_id": 1296269,
+                    "head_branch": "{head_branch}",
+                    "head_sha": "{head_sha}"
+                }}
+            }}"#
+        )
+    }
+
+    #[tokio::test]
+    async fn stops_at_the_newest_match() {
+        let server = MockServer::start().await;
+        let url = format!(
+            "{}/repos/octo-org/octo-docs/actions/artifacts",
+            server.uri()
+        );
+        let first_page = format!(
+            r#"{{ "total_count": 5, "artifacts": [{}, {}, {}, {}] }}"#,
+            artifact(5, "main", "328faa0", true),
+            artifact(4, "feature", "328faa0", false),
+            artifact(3, "main", "acb5820", false),
+            artifact(2, "main", "328faa0", false),
+        );
+        Mock::given(method("GET"))
+            .and(path("/repos/octo-org/octo-docs/actions/artifacts"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+                r#"{{ "total_count": 5, "artifacts": [{}] }}"#,
+                artifact(1, "main", "328faa0", false)
+            )))
+            .expect(0)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo-org/octo-docs/actions/artifacts"))
+            .and(query_param("name", "site"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("link", format!(r#"<{url}?name=site&page=2>; rel="next""#))
+                    .set_body_string(first_page),
+            )
+            .mount(&server)
+            .await;
+        let client = test_client(&server);
+
+        let query = LatestArtifactQuery::new("site")
+            .branch("main")
+            .head_sha("328faa0");
+        let latest = find_latest_artifact_with(&client, "octo-org", "octo-docs", &query)
+            .await
+            .unwrap();
+        assert_eq!(latest.id, 2);
+
+        let query = query.include_expired(true);
+        let latest = find_latest_artifact_with(&client, "octo-org", "octo-docs", &query)
+            .await
+            .unwrap();
+        assert_eq!(latest.id, 5);
+
+        let query = LatestArtifactQuery::new("site").branch("feature");
+        let latest = find_latest_artifact_with(&client, "octo-org", "octo-docs", &query)
+            .await
+            .unwrap();
+        assert_eq!(latest.id, 4);
+    }
+}
@@ -2,14 +2,39 @@
 
 #![cfg(feature = "transactions")]
 
+mod artifact_source;
+mod check_run;
+pub(crate) mod classify;
+mod commit_status;
+mod delete_artifact;
+mod deployment;
+mod dispatch_workflow;
 mod download_artifact;
 mod download_artifact_and_extract;
+mod download_run_logs;
+mod enforce_policy;
 mod extract_archive;
 mod fetch_artifact;
 mod fetch_artifacts;
+mod find_latest_artifact;
+mod select_artifacts;
+mod wait;
+mod workflow_runs;
 
+pub use artifact_source::*;
+pub use check_run::*;
+pub use commit_status::*;
+pub use delete_artifact::*;
+pub use deployment::*;
+pub use dispatch_workflow::*;
 pub use download_artifact::*;
 pub use download_artifact_and_extract::*;
+pub use download_run_logs::*;
+pub use enforce_policy::*;
 pub use extract_archive::*;
 pub use fetch_artifact::*;
 pub use fetch_artifacts::*;
+pub use find_latest_artifact::*;
+pub use select_artifacts::*;
+pub use wait::*;
+pub use workflow_runs::*;
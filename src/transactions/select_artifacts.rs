@@ -0,0 +1,121 @@
+use futures::TryStreamExt as _;
+use reqwest::Url;
+use tracing::{debug, error, info};
+
+use crate::{
+    framework::{StateError, StateResult},
+    transactions::fetch_artifacts::{artifacts_url, stream_artifacts_from},
+    workflow::{
+        artifact::Artifact,
+        client::{GITHUB_CLIENT, GitHubClient},
+        selector::ArtifactSelector,
+    },
+};
+
+/// Fetches the artifacts of a workflow run from GitHub that are selected by `selector`, using the shared [`GITHUB_CLIENT`].
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the artifacts fails, or cancelling if no artifact is selected.
+///
+/// See: [`select_artifacts_with`]
+pub async fn select_artifacts(
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+    selector: &ArtifactSelector,
+) -> StateResult<Vec<Artifact>> {
+    select_artifacts_with(&GITHUB_CLIENT, owner, repo, run_id, selector).await
+}
+
+/// Fetches the artifacts of a workflow run from GitHub that are selected by `selector`, using the given client.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the artifacts fails, or cancelling if no artifact is selected.
+pub async fn select_artifacts_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+    selector: &ArtifactSelector,
+) -> StateResult<Vec<Artifact>> {
+    let mut url = artifacts_url(client, owner, repo, run_id);
+    if let Some(name) = selector.exact_name()
+        && let Ok(mut parsed) = Url::parse(&url)
+    {
+        parsed.query_pairs_mut().append_pair("name", name);
+        url = parsed.into();
+    }
+    debug!("selecting artifacts by {selector} from {url}…");
+
+    let artifacts: Vec<Artifact> = stream_artifacts_from(client, url.clone())
+        .try_collect()
+        .await?;
+    let names = artifacts
+        .iter()
+        .map(|artifact| artifact.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let selected = artifacts
+        .iter()
+        .filter(|artifact| selector.matches(&artifact.name))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    match selected.len() {
+        0 => {
+            error!("no artifacts at {url} match {selector}! available: [{names}]");
+            Err(StateError::Cancelled)
+        }
+        count => {
+            info!("selected {count} artifacts by {selector} from {url}");
+            Ok(selected)
+        }
+    }
+}
+
+/// Fetches the only artifact of a workflow run from GitHub that is selected by `selector`, using the shared [`GITHUB_CLIENT`].
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the artifacts fails, or cancelling if no artifact or more than one artifact is selected.
+///
+/// See: [`select_artifact_with`]
+pub async fn select_artifact(
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+    selector: &ArtifactSelector,
+) -> StateResult<Artifact> {
+    select_artifact_with(&GITHUB_CLIENT, owner, repo, run_id, selector).await
+}
+
+/// Fetches the only artifact of a workflow run from GitHub that is selected by `selector`, using the given client.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the artifacts fails, or cancelling if no artifact or more than one artifact is selected.
+pub async fn select_artifact_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+    selector: &ArtifactSelector,
+) -> StateResult<Artifact> {
+    let mut selected = select_artifacts_with(client, owner, repo, run_id, selector).await?;
+    match selected.len() {
+        1 => Ok(selected.remove(0)),
+        count => {
+            let names = selected
+                .iter()
+                .map(|artifact| artifact.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            error!(
+                "ambiguous selection: {count} artifacts of run {run_id} match {selector}! matched: [{names}]"
+            );
+            Err(StateError::Cancelled)
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use futures::TryStreamExt as _;
+use tracing::{debug, error, info};
+
+use crate::{
+    framework::{StateError, StateResult, deadline},
+    transactions::{fetch_workflow_run_with, stream_artifacts},
+    workflow::{
+        RunConclusion, WorkflowRun,
+        artifact::Artifact,
+        client::{GITHUB_CLIENT, GitHubClient},
+        selector::ArtifactSelector,
+    },
+};
+
+/// Waits until a workflow run completes on GitHub using the shared [`GITHUB_CLIENT`], polling every `interval`.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the workflow run fails, or retrying if the deadline is reached. The conclusion is not checked.
+///
+/// See: [`wait_for_workflow_run_with`]
+pub async fn wait_for_workflow_run(
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+    after_attempt: Option<u64>,
+    interval: Duration,
+) -> StateResult<WorkflowRun> {
+    wait_for_workflow_run_with(&GITHUB_CLIENT, owner, repo, run_id, after_attempt, interval).await
+}
+
+/// Waits until a workflow run completes on GitHub using the given client, polling every `interval`.
+///
+/// After re-running, pass the attempt of the run before re-running as `after_attempt`, so that the previous attempt, which may still show as completed, is not mistaken for the new one.
+///
+/// Polling stops at the deadline of the current business, or never if unbounded. If the client caches responses, polls are conditional requests, which do not count against the rate limit while the run is unchanged.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the workflow run fails, or retrying if the deadline is reached. The conclusion is not checked.
+///
+/// See: [`deadline`], [`ResponseCache`](crate::workflow::cache::ResponseCache)
+pub async fn wait_for_workflow_run_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+    after_attempt: Option<u64>,
+    interval: Duration,
+) -> StateResult<WorkflowRun> {
+    let run = poll(&format!("workflow run {run_id}"), interval, || async {
+        let run = fetch_workflow_run_with(client, owner, repo, run_id).await?;
+        let is_new = after_attempt.is_none_or(|after_attempt| {
+            // A run without an attempt is on its first one
+            run.run_attempt.unwrap_or(1) > after_attempt
+        });
+        Ok((is_new && run.is_completed()).then_some(run))
+    })
+    .await?;
+
+    info!(
+        "workflow run {run} completed with {}",
+        run.conclusion
+            .as_ref()
+            .map_or("an unknown conclusion", RunConclusion::as_str)
+    );
+    Ok(run)
+}
+
+/// Waits until artifacts matching the selector are available for a workflow run on GitHub using the shared [`GITHUB_CLIENT`], polling every `interval`.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the artifacts fails, or retrying if the deadline is reached.
+///
+/// See: [`wait_for_artifacts_with`]
+pub async fn wait_for_artifacts(
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+    selector: &ArtifactSelector,
+    interval: Duration,
+) -> StateResult<Vec<Artifact>> {
+    wait_for_artifacts_with(&GITHUB_CLIENT, owner, repo, run_id, selector, interval).await
+}
+
+/// Waits until artifacts matching the selector are available for a workflow run on GitHub using the given client, polling every `interval`.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the artifacts fails, or retrying if the deadline is reached.
+///
+/// See: [`wait_for_workflow_run_with`]
+pub async fn wait_for_artifacts_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+    selector: &ArtifactSelector,
+    interval: Duration,
+) -> StateResult<Vec<Artifact>> {
+    let what = format!("artifacts matching {selector} of workflow run {run_id}");
+    poll(&what, interval, || async {
+        let artifacts: Vec<Artifact> = stream_artifacts(client, owner, repo, run_id)
+            .try_filter(|artifact| std::future::ready(selector.matches(&artifact.name)))
+            .try_collect()
+            .await?;
+        Ok((!artifacts.is_empty()).then_some(artifacts))
+    })
+    .await
+}
+
+/// Polls `f` every `interval` until it returns a value, the deadline of the current business is reached, or it fails.
+pub(crate) async fn poll<T, F, Fut>(what: &str, interval: Duration, mut f: F) -> StateResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = StateResult<Option<T>>>,
+{
+    loop {
+        if let Some(value) = f().await? {
+            return Ok(value);
+        }
+        if deadline::remaining().is_some_and(|remaining| remaining < interval) {
+            error!("stopped waiting for {what}: deadline reached!");
+            return Err(StateError::Retry);
+        }
+        debug!("waiting for {what}, polling again in {interval:?}…");
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path},
+    };
+
+    use super::*;
+    use crate::workflow::client::test_client;
+
+    #[tokio::test]
+    async fn waits_for_runs_without_attempts() {
+        let mut run: serde_json::Value =
+            serde_json::from_str(include_str!("../../tests/fixtures/workflow_run.json")).unwrap();
+        run["status"] = "completed".into();
+        run["conclusion"] = "success".into();
+        run.as_object_mut().unwrap().remove("run_attempt");
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo-org/octo-repo/actions/runs/30433642"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(run))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let run = wait_for_workflow_run_with(
+            &client,
+            "octo-org",
+            "octo-repo",
+            "30433642",
+            Some(0),
+            Duration::from_millis(10),
+        )
+        .await
+        .unwrap();
+        assert_eq!(run.run_attempt, None);
+    }
+}
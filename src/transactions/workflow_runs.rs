@@ -0,0 +1,378 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::{Stream, TryStreamExt as _, future};
+use reqwest::Url;
+use tracing::{debug, error, info};
+
+use crate::{
+    framework::{StateError, StateResult},
+    transactions::classify::classify,
+    workflow::{
+        RunConclusion, RunStatus, WorkflowRun, WorkflowRuns,
+        client::{GITHUB_CLIENT, GitHubClient},
+    },
+};
+
+/// The filters to list workflow runs.
+///
+/// # Examples
+///
+/// ```rust
+/// # use api_framework::{transactions::WorkflowRunQuery, workflow::RunConclusion};
+/// // the green runs on `main` triggered by pushes
+/// let query = WorkflowRunQuery::new()
+///     .branch("main")
+///     .event("push")
+///     .conclusion(RunConclusion::Success);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WorkflowRunQuery {
+    branch: Option<String>,
+    event: Option<String>,
+    status: Option<String>,
+    head_sha: Option<String>,
+    created_since: Option<DateTime<Utc>>,
+}
+
+impl WorkflowRunQuery {
+    /// Creates a [`WorkflowRunQuery`] without filters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only lists workflow runs of the branch.
+    pub fn branch<S>(mut self, branch: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.branch = Some(branch.into());
+        self
+    }
+
+    /// Only lists workflow runs triggered by the event, like `push` or `workflow_dispatch`.
+    pub fn event<S>(mut self, event: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// Only lists workflow runs with the status. Overrides [`Self::conclusion`].
+    pub fn status(mut self, status: RunStatus) -> Self {
+        self.status = Some(status.as_str().to_owned());
+        self
+    }
+
+    /// Only lists workflow runs with the conclusion. Overrides [`Self::status`].
+    pub fn conclusion(mut self, conclusion: RunConclusion) -> Self {
+        self.status = Some(conclusion.as_str().to_owned());
+        self
+    }
+
+    /// Only lists workflow runs of the commit.
+    pub fn head_sha<S>(mut self, head_sha: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.head_sha = Some(head_sha.into());
+        self
+    }
+
+    /// Only lists workflow runs created at or after the time.
+    pub const fn created_since(mut self, created_since: DateTime<Utc>) -> Self {
+        self.created_since = Some(created_since);
+        self
+    }
+
+    fn apply(&self, url: &str) -> String {
+        let Ok(mut url) = Url::parse(url) else {
+            return url.to_owned();
+        };
+        let created = self.created_since.map(|created_since| {
+            format!(
+                ">={}",
+                created_since.to_rfc3339_opts(SecondsFormat::Secs, true)
+            )
+        });
+        {
+            let mut pairs = url.query_pairs_mut();
+            for (key, value) in [
+                ("branch", self.branch.as_deref()),
+                ("event", self.event.as_deref()),
+                ("status", self.status.as_deref()),
+                ("head_sha", self.head_sha.as_deref()),
+                ("created", created.as_deref()),
+            ] {
+                if let Some(value) = value {
+                    pairs.append_pair(key, value);
+                }
+            }
+        }
+        url.into()
+    }
+}
+
+/// Streams the runs of a workflow from GitHub using the shared [`GITHUB_CLIENT`], newest first, following the pagination until the last page.
+///
+/// The workflow is either the file name of the workflow, like `deploy.yml`, or its ID.
+///
+/// # Errors
+///
+/// Yields an error that instructs retrying or cancelling if fetching a page fails, after which the stream ends.
+///
+/// See: [`list_workflow_runs_with`]
+pub fn list_workflow_runs(
+    owner: &str,
+    repo: &str,
+    workflow: &str,
+    query: &WorkflowRunQuery,
+) -> impl Stream<Item = StateResult<WorkflowRun>> + Send + 'static {
+    list_workflow_runs_with(&GITHUB_CLIENT, owner, repo, workflow, query)
+}
+
+/// Streams the runs of a workflow from GitHub using the given client, newest first, following the pagination until the last page.
+///
+/// The workflow is either the file name of the workflow, like `deploy.yml`, or its ID.
+///
+/// # Errors
+///
+/// Yields an error that instructs retrying or cancelling if fetching a page fails, after which the stream ends.
+pub fn list_workflow_runs_with<'a>(
+    client: &'a GitHubClient,
+    owner: &str,
+    repo: &str,
+    workflow: &str,
+    query: &WorkflowRunQuery,
+) -> impl Stream<Item = StateResult<WorkflowRun>> + Send + 'a {
+    let url = query.apply(&client.url(&format!(
+        "/repos/{owner}/{repo}/actions/workflows/{workflow}/runs"
+    )));
+    debug!("listing workflow runs from {url}…");
+
+    client.paginate::<WorkflowRuns>(&url).map_err(move |err| {
+        error!("failed to list workflow runs from {url}: {err:#}");
+        classify(&err)
+    })
+}
+
+/// Fetches a workflow run from GitHub using the shared [`GITHUB_CLIENT`].
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the workflow run fails.
+///
+/// See: [`fetch_workflow_run_with`]
+pub async fn fetch_workflow_run(owner: &str, repo: &str, run_id: &str) -> StateResult<WorkflowRun> {
+    fetch_workflow_run_with(&GITHUB_CLIENT, owner, repo, run_id).await
+}
+
+/// Fetches a workflow run from GitHub using the given client.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if fetching the workflow run fails.
+pub async fn fetch_workflow_run_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    run_id: &str,
+) -> StateResult<WorkflowRun> {
+    let url = client.url(&format!("/repos/{owner}/{repo}/actions/runs/{run_id}"));
+    debug!("fetching workflow run from {url}…");
+
+    match client.get_json::<WorkflowRun>(&url).await {
+        Ok(run) => {
+            info!("fetched workflow run {run} from {url}");
+            Ok(run)
+        }
+        Err(err) => {
+            error!("failed to fetch workflow run from {url}: {err:#}");
+            Err(classify(&err))
+        }
+    }
+}
+
+/// Finds the latest successful run of a workflow on a branch from GitHub using the shared [`GITHUB_CLIENT`].
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if listing the workflow runs fails, or cancelling if there is no successful run.
+///
+/// See: [`find_latest_successful_run_with`]
+pub async fn find_latest_successful_run(
+    owner: &str,
+    repo: &str,
+    workflow: &str,
+    branch: &str,
+) -> StateResult<WorkflowRun> {
+    find_latest_successful_run_with(&GITHUB_CLIENT, owner, repo, workflow, branch).await
+}
+
+/// Finds the latest successful run of a workflow on a branch from GitHub using the given client.
+///
+/// # Errors
+///
+/// Returns an error that instructs retrying or cancelling if listing the workflow runs fails, or cancelling if there is no successful run.
+///
+/// See: [`list_workflow_runs_with`]
+pub async fn find_latest_successful_run_with(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    workflow: &str,
+    branch: &str,
+) -> StateResult<WorkflowRun> {
+    let query = WorkflowRunQuery::new()
+        .branch(branch)
+        .conclusion(RunConclusion::Success);
+
+    // The runs are listed newest first, so pages are only fetched until the first successful run
+    let runs = list_workflow_runs_with(client, owner, repo, workflow, &query)
+        .try_filter(|run| future::ready(run.conclusion == Some(RunConclusion::Success)));
+    futures::pin_mut!(runs);
+    match runs.try_next().await? {
+        Some(run) => {
+            info!("found the latest successful run {run} of {workflow} on {branch}");
+            Ok(run)
+        }
+        None => {
+            error!("no successful runs of {workflow} on {branch} in {owner}/{repo}!");
+            Err(StateError::Cancelled)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path, query_param},
+    };
+
+    use super::*;
+    use crate::workflow::client::test_client;
+
+    const RUNS: &str = "/repos/octo-org/octo-repo/actions/workflows/build.yml/runs";
+
+    fn run(id: u64, conclusion: &str) -> serde_json::Value {
+        let mut run: serde_json::Value =
+            serde_json::from_str(include_str!("../../tests/fixtures/workflow_run.json")).unwrap();
+        run["id"] = id.into();
+        run["status"] = "completed".into();
+        run["conclusion"] = conclusion.into();
+        run
+    }
+
+    fn page(runs: &[serde_json::Value]) -> serde_json::Value {
+        serde_json::json!({ "total_count": 3, "workflow_runs": runs })
+    }
+
+    #[tokio::test]
+    async fn lists_workflow_runs_across_pages() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(RUNS))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page(&[run(1, "success")])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(RUNS))
+            .and(query_param("branch", "main"))
+            .and(query_param("event", "push"))
+            .and(query_param("status", "completed"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header(
+                        "link",
+                        format!(r#"<{}{RUNS}?page=2>; rel="next""#, server.uri()).as_str(),
+                    )
+                    .set_body_json(page(&[run(3, "success"), run(2, "failure")])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let query = WorkflowRunQuery::new()
+            .branch("main")
+            .event("push")
+            .status(RunStatus::Completed);
+        let runs: Vec<WorkflowRun> =
+            list_workflow_runs_with(&client, "octo-org", "octo-repo", "build.yml", &query)
+                .try_collect()
+                .await
+                .unwrap();
+        assert_eq!(runs.iter().map(|run| run.id).collect::<Vec<_>>(), [3, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn fetches_workflow_runs() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo-org/octo-repo/actions/runs/30433642"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(run(30433642, "success")))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let run = fetch_workflow_run_with(&client, "octo-org", "octo-repo", "30433642")
+            .await
+            .unwrap();
+        assert_eq!(run.id, 30433642);
+        assert_eq!(run.conclusion, Some(RunConclusion::Success));
+
+        assert_eq!(
+            fetch_workflow_run_with(&client, "octo-org", "octo-repo", "1").await,
+            Err(StateError::Cancelled)
+        );
+    }
+
+    #[tokio::test]
+    async fn finds_the_latest_successful_run() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(RUNS))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page(&[run(1, "success")])))
+            .expect(0)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(RUNS))
+            .and(query_param("branch", "main"))
+            .and(query_param("status", "success"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header(
+                        "link",
+                        format!(r#"<{}{RUNS}?page=2>; rel="next""#, server.uri()).as_str(),
+                    )
+                    .set_body_json(page(&[run(3, "failure"), run(2, "success")])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(RUNS))
+            .and(query_param("branch", "dev"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page(&[run(4, "failure")])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let run =
+            find_latest_successful_run_with(&client, "octo-org", "octo-repo", "build.yml", "main")
+                .await
+                .unwrap();
+        assert_eq!(run.id, 2);
+
+        assert_eq!(
+            find_latest_successful_run_with(&client, "octo-org", "octo-repo", "build.yml", "dev")
+                .await,
+            Err(StateError::Cancelled)
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use reqwest::StatusCode;
+
+/// An error that occurs when receiving a webhook delivery.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum WebhookError {
+    /// A required header is missing or not valid UTF-8.
+    MissingHeader(&'static str),
+    /// The signature does not match the body, or is malformed.
+    InvalidSignature,
+    /// The payload fails to parse as the announced event.
+    InvalidPayload(serde_json::Error),
+}
+
+impl WebhookError {
+    /// Gets the status to respond with.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::InvalidSignature => StatusCode::UNAUTHORIZED,
+            Self::MissingHeader(_) | Self::InvalidPayload(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHeader(name) => write!(f, "missing header {name}"),
+            Self::InvalidSignature => write!(f, "invalid signature"),
+            Self::InvalidPayload(err) => write!(f, "invalid payload: {err}"),
+        }
+    }
+}
+
+impl Error for WebhookError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InvalidPayload(err) => Some(err),
+            _ => None,
+        }
+    }
+}
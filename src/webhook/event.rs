@@ -0,0 +1,130 @@
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    webhook::WebhookError,
+    workflow::{Actor, WorkflowRun},
+};
+
+/// A webhook event from GitHub, parsed by the name in the `X-GitHub-Event` header.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookEvent {
+    /// Sent once the webhook is created.
+    Ping(PingEvent),
+    /// Sent when a workflow run is requested, in progress or completed.
+    WorkflowRun(Box<WorkflowRunEvent>),
+    /// Any other event, kept by its name.
+    Other(String),
+}
+
+impl WebhookEvent {
+    /// Parses a payload by the name of the event.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebhookError::InvalidPayload`] if the payload fails to parse as the event.
+    pub fn parse(name: &str, body: &[u8]) -> Result<Self, WebhookError> {
+        let parsed = match name {
+            "ping" => serde_json::from_slice(body).map(Self::Ping),
+            "workflow_run" => serde_json::from_slice(body).map(Self::WorkflowRun),
+            name => Ok(Self::Other(name.to_owned())),
+        };
+        parsed.map_err(WebhookError::InvalidPayload)
+    }
+
+    /// Takes the workflow run event if it is completed.
+    pub fn into_completed_run(self) -> Option<WorkflowRunEvent> {
+        match self {
+            Self::WorkflowRun(event) if event.is_completed() => Some(*event),
+            _ => None,
+        }
+    }
+}
+
+/// The payload of a `ping` event.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PingEvent {
+    /// A random string of GitHub zen.
+    pub zen: String,
+    /// The ID of the webhook.
+    pub hook_id: u64,
+}
+
+/// The payload of a `workflow_run` event.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct WorkflowRunEvent {
+    /// The activity that triggered the event.
+    pub action: WorkflowRunAction,
+    /// The workflow run.
+    pub workflow_run: WorkflowRun,
+    /// The repository of the workflow run.
+    pub repository: Repository,
+}
+
+impl WorkflowRunEvent {
+    /// Checks if the workflow run is completed, regardless of its conclusion.
+    pub fn is_completed(&self) -> bool {
+        self.action == WorkflowRunAction::Completed
+    }
+}
+
+impl Display for WorkflowRunEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "workflow run {} of {} {}",
+            self.workflow_run, self.repository, self.action
+        )
+    }
+}
+
+/// The activity of a `workflow_run` event.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowRunAction {
+    /// The workflow run is requested.
+    Requested,
+    /// The workflow run is in progress.
+    InProgress,
+    /// The workflow run is completed.
+    Completed,
+    /// An activity unknown to this crate, kept as sent by GitHub.
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl Display for WorkflowRunAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Requested => "requested",
+            Self::InProgress => "in progress",
+            Self::Completed => "completed",
+            Self::Unknown(action) => action,
+        })
+    }
+}
+
+/// Represents a repository from GitHub webhooks.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Repository {
+    /// The unique identifier of the repository.
+    pub id: u64,
+    /// The name of the repository.
+    pub name: String,
+    /// The full name of the repository, like `octocat/hello-world`.
+    pub full_name: String,
+    /// The owner of the repository.
+    pub owner: Actor,
+}
+
+impl Display for Repository {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.full_name)
+    }
+}
@@ -0,0 +1,15 @@
+//! A receiver of GitHub webhooks, verifying their signatures and handing completed workflow runs to a [`QueuedAsyncFramework`](crate::framework::queued_async::QueuedAsyncFramework).
+//!
+//! The receiver is agnostic of web frameworks: pass the headers and the raw body of a delivery to [`WebhookReceiver::receive`], and respond with [`WebhookError::status`] if it fails.
+
+#![cfg(feature = "webhook")]
+
+mod error;
+mod event;
+mod receiver;
+mod signature;
+
+pub use error::*;
+pub use event::*;
+pub use receiver::*;
+pub use signature::*;
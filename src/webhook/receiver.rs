@@ -0,0 +1,169 @@
+use std::pin::Pin;
+
+use reqwest::header::HeaderMap;
+use tracing::{debug, info};
+
+use crate::{
+    env::Secret,
+    framework::{
+        StateResult,
+        queued_async::{QueuedAsyncFramework, QueuedAsyncFrameworkContext},
+    },
+    webhook::{SIGNATURE_HEADER, WebhookError, WebhookEvent, WorkflowRunEvent, verify_signature},
+};
+
+/// The header carrying the name of the event.
+pub const EVENT_HEADER: &str = "X-GitHub-Event";
+/// The header carrying the unique identifier of the delivery.
+pub const DELIVERY_HEADER: &str = "X-GitHub-Delivery";
+
+/// Receives GitHub webhook deliveries, verifying their signatures with a secret.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::sync::LazyLock;
+/// # use api_framework::{framework::{StateResult, queued_async::*}, webhook::*};
+/// # use reqwest::header::HeaderMap;
+/// static FRAMEWORK: LazyLock<QueuedAsyncFramework<String>> = LazyLock::new(QueuedAsyncFramework::new);
+///
+/// async fn handle(receiver: &WebhookReceiver, headers: &HeaderMap, body: &[u8]) -> Result<(), WebhookError> {
+///     if let Some(event) = receiver.receive(headers, body)?.into_completed_run() {
+///         // Responds before deploying, as GitHub waits for at most 10 seconds
+///         tokio::spawn(event.dispatch(&FRAMEWORK, |cx, event| Box::pin(deploy(cx, event))));
+///     }
+///     Ok(())
+/// }
+///
+/// async fn deploy(cx: QueuedAsyncFrameworkContext, event: WorkflowRunEvent) -> StateResult<()> {
+///     cx.check(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct WebhookReceiver {
+    secret: Secret<String>,
+}
+
+impl WebhookReceiver {
+    /// Creates a [`WebhookReceiver`] verifying deliveries with `secret`.
+    pub const fn new(secret: Secret<String>) -> Self {
+        Self { secret }
+    }
+
+    /// Creates a [`WebhookReceiver`] with the secret from `GITHUB_WEBHOOK_SECRET`, or the file at `GITHUB_WEBHOOK_SECRET_FILE`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the secret is missing.
+    pub fn from_env() -> anyhow::Result<Self> {
+        Secret::from_env("GITHUB_WEBHOOK_SECRET").map(Self::new)
+    }
+
+    /// Receives a delivery from its headers and raw body, verifying the signature before parsing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a header is missing, the signature is invalid, or the payload fails to parse.
+    pub fn receive(&self, headers: &HeaderMap, body: &[u8]) -> Result<WebhookEvent, WebhookError> {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .ok_or(WebhookError::MissingHeader(name))
+        };
+
+        let signature = header(SIGNATURE_HEADER)?;
+        verify_signature(&self.secret, body, signature)?;
+
+        let name = header(EVENT_HEADER)?;
+        let delivery = header(DELIVERY_HEADER).unwrap_or("unknown");
+        debug!("received {name} event from delivery {delivery}");
+
+        WebhookEvent::parse(name, body)
+    }
+}
+
+impl WorkflowRunEvent {
+    /// Runs transactions for the workflow run in a [`QueuedAsyncFramework`], keyed by the full name of the repository, so that only the latest run of each repository is deployed.
+    ///
+    /// The event is cloned for each attempt.
+    ///
+    /// # Errors
+    ///
+    /// Returns the final result of the transaction as-is.
+    pub async fn dispatch<F, R>(
+        self,
+        framework: &QueuedAsyncFramework<String>,
+        f: F,
+    ) -> StateResult<R>
+    where
+        F: Fn(
+                QueuedAsyncFrameworkContext,
+                Self,
+            ) -> Pin<Box<dyn Future<Output = StateResult<R>> + Send>>
+            + Send
+            + Sync,
+    {
+        info!("dispatching {self}…");
+        let id = self.repository.full_name.clone();
+        let name = format!("{} run {}", self.repository, self.workflow_run);
+        framework
+            .run_with_name(id, name, move |cx| f(cx, self.clone()))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+    use crate::webhook::{WorkflowRunAction, sign};
+
+    #[tokio::test]
+    async fn dispatches_completed_runs() {
+        let body = include_bytes!("../../tests/fixtures/workflow_run_completed.json");
+        let secret = Secret::new(String::from("secret"));
+        let mut headers = HeaderMap::new();
+        headers.insert(EVENT_HEADER, HeaderValue::from_static("workflow_run"));
+        headers.insert(
+            SIGNATURE_HEADER,
+            HeaderValue::from_str(&sign(&secret, body)).unwrap(),
+        );
+
+        let receiver = WebhookReceiver::new(secret);
+        assert!(matches!(
+            receiver.receive(&headers, b"{}"),
+            Err(WebhookError::InvalidSignature)
+        ));
+
+        let event = receiver
+            .receive(&headers, body)
+            .unwrap()
+            .into_completed_run()
+            .unwrap();
+        assert!(event.workflow_run.is_successful());
+        assert_eq!(event.workflow_run.repository_id, event.repository.id);
+
+        let framework = QueuedAsyncFramework::new();
+        let repository = event
+            .dispatch(&framework, |_, event| {
+                Box::pin(async move { Ok(event.repository.full_name) })
+            })
+            .await;
+        assert_eq!(repository.as_deref(), Ok("octocat/Hello-World"));
+
+        // Unknown activities are kept as sent
+        let body = String::from_utf8_lossy(body).replace(r#""completed""#, r#""paused""#);
+        let Ok(WebhookEvent::WorkflowRun(event)) =
+            WebhookEvent::parse("workflow_run", body.as_bytes())
+        else {
+            panic!("failed to parse the workflow_run event");
+        };
+        assert_eq!(
+            event.action,
+            WorkflowRunAction::Unknown(String::from("paused"))
+        );
+        assert_eq!(event.action.to_string(), "paused");
+    }
+}
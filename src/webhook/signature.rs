@@ -0,0 +1,56 @@
+use hmac::{Hmac, Mac as _};
+use sha2::Sha256;
+
+use crate::{env::Secret, webhook::WebhookError};
+
+/// The header carrying the HMAC-SHA256 signature of a delivery, like `sha256=...`.
+pub const SIGNATURE_HEADER: &str = "X-Hub-Signature-256";
+
+/// Signs a payload with the webhook secret, in the format of [`SIGNATURE_HEADER`].
+pub fn sign(secret: &Secret<String>, body: &[u8]) -> String {
+    let mut mac = hmac(secret);
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Verifies the signature of a payload against the webhook secret, in constant time.
+///
+/// # Errors
+///
+/// Returns [`WebhookError::InvalidSignature`] if the signature is malformed or does not match.
+pub fn verify_signature(
+    secret: &Secret<String>,
+    body: &[u8],
+    signature: &str,
+) -> Result<(), WebhookError> {
+    let signature = signature
+        .strip_prefix("sha256=")
+        .and_then(|signature| hex::decode(signature).ok())
+        .ok_or(WebhookError::InvalidSignature)?;
+
+    let mut mac = hmac(secret);
+    mac.update(body);
+    mac.verify_slice(&signature)
+        .map_err(|_| WebhookError::InvalidSignature)
+}
+
+fn hmac(secret: &Secret<String>) -> Hmac<Sha256> {
+    Hmac::new_from_slice(secret.expose().as_bytes()).expect("HMAC accepts keys of any length")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_signatures() {
+        // From https://docs.github.com/en/webhooks/using-webhooks/validating-webhook-deliveries
+        let secret = Secret::new(String::from("It's a Secret to Everybody"));
+        let signature = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+        assert_eq!(sign(&secret, b"Hello, World!"), signature);
+        assert!(verify_signature(&secret, b"Hello, World!", signature).is_ok());
+        assert!(verify_signature(&secret, b"Hello, World?", signature).is_err());
+        assert!(verify_signature(&secret, b"Hello, World!", "sha256=zz").is_err());
+        assert!(verify_signature(&secret, b"Hello, World!", &signature[7..]).is_err());
+    }
+}
@@ -2,35 +2,168 @@
 
 use std::fmt::Display;
 
-use reqwest::{RequestBuilder, header};
-use serde::Deserialize;
+use chrono::{DateTime, TimeDelta, Utc};
+use reqwest::RequestBuilder;
+use serde::{Deserialize, Serialize};
 
-use crate::{env::GITHUB_TOKEN, workflow::WorkflowRun};
+use crate::{
+    env::GITHUB_TOKEN,
+    workflow::{
+        WorkflowRun,
+        client::{GITHUB_CLIENT, GitHubRequest, with_github_headers},
+        pagination::Page,
+    },
+};
+
+/// How long before expiry downloading an artifact is warned about.
+pub const EXPIRY_WARNING: TimeDelta = TimeDelta::hours(1);
 
 /// Represents artifacts from GitHub REST API.
-#[derive(Debug, Deserialize, Clone)]
+///
+/// Serialized in the same format as GitHub REST API. Unknown fields are ignored when deserializing.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct Artifacts {
-    pub total_count: u8,
+    /// The total count of artifacts, across all pages.
+    #[serde(default)]
+    pub total_count: u64,
+    /// The artifacts.
     pub artifacts: Vec<Artifact>,
 }
 
 /// Represents an artifact from GitHub REST API.
-#[derive(Debug, Deserialize, Clone)]
+///
+/// Serialized in the same format as GitHub REST API, so that it can be persisted or forwarded. Unknown fields are ignored when deserializing.
+///
+/// # Examples
+///
+/// ```rust
+/// # use api_framework::workflow::artifact::Artifact;
+/// let artifact = Artifact::new(1, "site", "https://api.github.com/repos/octocat/hello/actions/artifacts/1")
+///     .with_digest("sha256:0000");
+/// assert_eq!(artifact.archive_download_url, "https://api.github.com/repos/octocat/hello/actions/artifacts/1/zip");
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct Artifact {
+    /// The unique identifier of the artifact.
     pub id: u64,
+    /// The GraphQL node ID of the artifact.
+    #[serde(default)]
     pub node_id: String,
+    /// The name of the artifact.
     pub name: String,
+    /// The size of the artifact in bytes.
+    #[serde(default)]
     pub size_in_bytes: u64,
+    /// The REST API URL of the artifact.
+    #[serde(default)]
     pub url: String,
+    /// The URL to download the archive of the artifact.
     pub archive_download_url: String,
+    /// Whether the artifact has expired.
+    #[serde(default)]
     pub expired: bool,
-    pub created_at: Option<String>,
-    pub expires_at: Option<String>,
-    pub updated_at: Option<String>,
+    /// The time when the artifact was created.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// The time when the artifact expires.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// The time when the artifact was last updated.
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// The SHA-256 digest of the archive, prefixed with `sha256:`.
+    #[serde(default)]
     pub digest: Option<String>,
+    /// The workflow run that produced the artifact.
+    #[serde(default)]
     pub workflow_run: Option<WorkflowRun>,
 }
 
+impl Artifacts {
+    /// Creates [`Artifacts`] of a single page.
+    pub fn new(artifacts: Vec<Artifact>) -> Self {
+        Self {
+            total_count: artifacts.len() as u64,
+            artifacts,
+        }
+    }
+}
+
+impl Artifact {
+    /// Creates an [`Artifact`] at the REST API URL `url`, downloaded from `{url}/zip`. The other fields are empty.
+    pub fn new<S, U>(id: u64, name: S, url: U) -> Self
+    where
+        S: Into<String>,
+        U: Into<String>,
+    {
+        let url = url.into();
+        Self {
+            id,
+            node_id: String::new(),
+            name: name.into(),
+            size_in_bytes: 0,
+            archive_download_url: format!("{url}/zip"),
+            url,
+            expired: false,
+            created_at: None,
+            expires_at: None,
+            updated_at: None,
+            digest: None,
+            workflow_run: None,
+        }
+    }
+
+    /// Sets the SHA-256 digest of the archive, prefixed with `sha256:`.
+    pub fn with_digest<S>(mut self, digest: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.digest = Some(digest.into());
+        self
+    }
+
+    /// Sets the time when the artifact was created.
+    pub fn with_created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    /// Sets the time when the artifact expires.
+    pub fn with_expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Sets the workflow run that produced the artifact.
+    pub fn with_workflow_run(mut self, workflow_run: WorkflowRun) -> Self {
+        self.workflow_run = Some(workflow_run);
+        self
+    }
+
+    /// Checks if the artifact has expired, either marked by GitHub or past [`Self::expires_at`].
+    pub fn is_expired(&self) -> bool {
+        self.expired
+            || self
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= Utc::now())
+    }
+
+    /// Gets the time remaining until the artifact expires, or [`None`] if unknown. Negative if already past.
+    pub fn expires_in(&self) -> Option<TimeDelta> {
+        self.expires_at.map(|expires_at| expires_at - Utc::now())
+    }
+}
+
+impl Page for Artifacts {
+    type Item = Artifact;
+
+    fn into_items(self) -> Vec<Self::Item> {
+        self.artifacts
+    }
+}
+
 impl Display for Artifact {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -41,12 +174,56 @@ impl Display for Artifact {
     }
 }
 
-/// Builds a request for GitHub REST API.
+/// Builds a GET request for GitHub REST API with the shared [`GITHUB_CLIENT`].
+///
+/// # Errors
+///
+/// Returns an error if a token cannot be obtained for the owner of the requested repository.
+pub async fn github_api_request(url: &str) -> anyhow::Result<GitHubRequest<'static>> {
+    GITHUB_CLIENT.get(url).await
+}
+
+/// Builds a request for GitHub REST API, authenticated with [`GITHUB_TOKEN`].
+#[deprecated(note = "only supports `GITHUB_TOKEN`, use `github_api_request` instead")]
 pub fn github_api_request_builder(url: &str) -> RequestBuilder {
-    reqwest::Client::new()
-        .get(url)
-        .header(header::ACCEPT, "application/vnd.github+json")
-        .bearer_auth(&*GITHUB_TOKEN)
-        .header("X-GitHub-Api-Version", "2022-11-28")
-        .header("User-Agent", "KessokuTeaTime-API/1.0")
+    with_github_headers(reqwest::Client::new().get(url)).bearer_auth(GITHUB_TOKEN.expose())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let json = r#"{
+            "total_count": 1,
+            "artifacts": [{
+                "id": 11,
+                "node_id": "MDg6QXJ0aWZhY3QxMQ==",
+                "name": "site",
+                "size_in_bytes": 556,
+                "url": "https://api.github.com/repos/octo-org/octo-docs/actions/artifacts/11",
+                "archive_download_url": "https://api.github.com/repos/octo-org/octo-docs/actions/artifacts/11/zip",
+                "expired": false,
+                "created_at": "2020-01-10T14:59:22Z",
+                "expires_at": "2020-03-21T14:59:22Z",
+                "updated_at": "2020-02-21T14:59:22Z",
+                "digest": "sha256:cfc3236bdad15b5898bca8408945c9e19e1917da8704adc20eaa618444290a8c",
+                "workflow_run": {
+                    "id": 2332938,
+                    "repository_id": 1296269,
+                    "head_repository_id": 1296269,
+                    "head_branch": "main",
+                    "head_sha": "328faa0536e6fef19753d9d91dc96a9931694ce3",
+                    "some_future_field": true
+                }
+            }]
+        }"#;
+        let artifacts: Artifacts = serde_json::from_str(json).unwrap();
+        let serialized = serde_json::to_string(&artifacts).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Artifacts>(&serialized).unwrap(),
+            artifacts
+        );
+    }
 }
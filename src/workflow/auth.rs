@@ -0,0 +1,640 @@
+//! Authentication with GitHub REST API, through static tokens or a GitHub App.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use anyhow::bail;
+use chrono::Utc;
+use parking_lot::Mutex;
+use reqwest::header::{HeaderMap, HeaderValue};
+use sha2::Digest as _;
+use tracing::{debug, warn};
+
+use crate::{
+    env::{self, Secret},
+    workflow::rate_limit::RateLimit,
+};
+
+#[cfg(feature = "github_app")]
+pub use app::*;
+
+/// The credentials to authenticate with GitHub REST API.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum GitHubAuth {
+    /// A static token, like a personal access token.
+    Token(Secret<String>),
+    /// Several static tokens, rotated by their remaining rate limits.
+    Pool(TokenPool),
+    /// A GitHub App, exchanging installation tokens per owner.
+    #[cfg(feature = "github_app")]
+    App(GitHubApp),
+}
+
+impl GitHubAuth {
+    /// Selects the authentication from environment variables.
+    ///
+    /// In order of precedence:
+    ///
+    /// - A GitHub App, if `GITHUB_APP_ID` is set, along with `GITHUB_APP_PRIVATE_KEY` and an optional `GITHUB_APP_INSTALLATION_ID`.
+    /// - A [`TokenPool`], if `GITHUB_TOKENS` (or `GITHUB_TOKENS_FILE`) is set to tokens delimited by commas or line breaks.
+    /// - A single token from `GITHUB_TOKEN`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if none is configured, or the configuration is invalid.
+    pub fn from_env() -> anyhow::Result<Self> {
+        if env::var("GITHUB_APP_ID").is_ok() {
+            #[cfg(feature = "github_app")]
+            return GitHubApp::from_env().map(Self::App);
+            #[cfg(not(feature = "github_app"))]
+            bail!("GITHUB_APP_ID is set, but the `github_app` feature is disabled");
+        }
+
+        if env::var("GITHUB_TOKENS").is_ok() || env::var("GITHUB_TOKENS_FILE").is_ok() {
+            // Fails rather than falling back to `GITHUB_TOKEN` if the file cannot be read
+            let tokens = Secret::from_env("GITHUB_TOKENS")?;
+            let tokens = tokens
+                .expose()
+                .split([',', '\n'])
+                .map(str::trim)
+                .filter(|token| !token.is_empty())
+                .map(|token| Secret::new(token.to_owned()))
+                .collect::<Vec<_>>();
+            if tokens.is_empty() {
+                bail!("GITHUB_TOKENS is set, but contains no tokens");
+            }
+            return Ok(Self::Pool(TokenPool::new(tokens)));
+        }
+
+        Ok(Self::Token(env::load_github_token()?))
+    }
+
+    /// Gets a token to access the resources of `owner`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a GitHub App fails to obtain an installation token.
+    #[cfg_attr(not(feature = "github_app"), allow(unused_variables))]
+    pub async fn token(&self, owner: &str) -> anyhow::Result<AuthToken> {
+        match self {
+            Self::Token(token) => Ok(AuthToken::new(token.clone(), None, None)),
+            Self::Pool(pool) => Ok(pool.token()),
+            #[cfg(feature = "github_app")]
+            Self::App(app) => {
+                if owner.is_empty() {
+                    return Err(AuthError::UnknownOwner.into());
+                }
+                app.installation_token(owner)
+                    .await
+                    .map(|token| AuthToken::new(token, None, Some(owner.to_owned())))
+            }
+        }
+    }
+
+    /// Records the rate limit of a token from the headers of a response to a request authenticated by it.
+    pub fn observe(&self, token: &AuthToken, headers: &HeaderMap) {
+        if let (Self::Pool(pool), Some(slot)) = (self, token.slot) {
+            pool.observe(slot, headers);
+        }
+    }
+}
+
+/// An error obtaining a token that retrying cannot fix.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// The owner of the requested resource is unknown, so a GitHub App cannot pick an installation.
+    UnknownOwner,
+    /// The GitHub App is not installed for the owner.
+    NotInstalled {
+        /// The ID of the app.
+        app_id: String,
+        /// The owner.
+        owner: String,
+    },
+}
+
+impl Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownOwner => write!(
+                f,
+                "cannot authenticate as a GitHub App without knowing the owner"
+            ),
+            Self::NotInstalled { app_id, owner } => {
+                write!(f, "GitHub App {app_id} is not installed for {owner}")
+            }
+        }
+    }
+}
+
+impl Error for AuthError {}
+
+/// A token obtained from [`GitHubAuth`].
+#[derive(Debug, Clone)]
+pub struct AuthToken {
+    token: Secret<String>,
+    slot: Option<usize>,
+    /// The owner of the installation, for tokens of a GitHub App.
+    owner: Option<String>,
+}
+
+impl AuthToken {
+    const fn new(token: Secret<String>, slot: Option<usize>, owner: Option<String>) -> Self {
+        Self { token, slot, owner }
+    }
+
+    /// Exposes the token. Be careful not to log or persist it.
+    pub fn expose(&self) -> &str {
+        self.token.expose()
+    }
+
+    /// Gets the slot of the token in a [`TokenPool`], if picked from one.
+    pub(crate) const fn slot(&self) -> Option<usize> {
+        self.slot
+    }
+
+    /// Builds the sensitive `Authorization` header of the token. Returns [`None`] if the token is not a valid header value.
+    pub(crate) fn authorization(&self) -> Option<HeaderValue> {
+        let mut value = HeaderValue::from_str(&format!("Bearer {}", self.expose())).ok()?;
+        value.set_sensitive(true);
+        Some(value)
+    }
+
+    /// Identifies the credential, so that responses cached for one are never served to another.
+    ///
+    /// Installation tokens of a GitHub App are identified by their owner, as they are replaced before expiring. Static tokens are identified by their SHA-256 digest.
+    pub(crate) fn scope(&self) -> String {
+        match &self.owner {
+            Some(owner) => format!("installation:{owner}"),
+            None => format!(
+                "token:{}",
+                hex::encode(sha2::Sha256::digest(self.token.expose()))
+            ),
+        }
+    }
+}
+
+/// Several static tokens, rotated by their remaining rate limits.
+///
+/// Each request picks the token with the most remaining requests, treating tokens without known rate limits as healthy. If all tokens are exhausted, the one that resets first is used.
+#[derive(Debug)]
+pub struct TokenPool {
+    tokens: Vec<Secret<String>>,
+    rate_limits: Mutex<Vec<Option<RateLimit>>>,
+}
+
+impl TokenPool {
+    /// Creates a [`TokenPool`] from tokens.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tokens` is empty.
+    pub fn new(tokens: Vec<Secret<String>>) -> Self {
+        assert!(
+            !tokens.is_empty(),
+            "a token pool requires at least one token"
+        );
+        let rate_limits = Mutex::new(vec![None; tokens.len()]);
+        Self {
+            tokens,
+            rate_limits,
+        }
+    }
+
+    /// Gets the last known rate limit of each token, in the order they were provided.
+    pub fn rate_limits(&self) -> Vec<Option<RateLimit>> {
+        self.rate_limits.lock().clone()
+    }
+
+    /// Checks if all tokens are known to be rate limited.
+    pub fn is_exhausted(&self) -> bool {
+        self.rate_limits
+            .lock()
+            .iter()
+            .all(|rate_limit| rate_limit.is_some_and(|rate_limit| rate_limit.is_exhausted()))
+    }
+
+    /// Gets the number of tokens.
+    pub(crate) fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub(crate) fn token(&self) -> AuthToken {
+        let slot = self.select();
+        AuthToken::new(self.tokens[slot].clone(), Some(slot), None)
+    }
+
+    fn select(&self) -> usize {
+        let now = Utc::now();
+        let rate_limits = self.rate_limits.lock();
+
+        let healthiest = rate_limits
+            .iter()
+            .enumerate()
+            .max_by_key(|&(slot, rate_limit)| {
+                let remaining = match rate_limit {
+                    Some(rate_limit) if rate_limit.reset > now => rate_limit.remaining,
+                    _ => u32::MAX,
+                };
+                // Prefers earlier tokens on ties
+                (remaining, std::cmp::Reverse(slot))
+            })
+            .map(|(slot, _)| slot)
+            .unwrap_or_default();
+
+        match rate_limits[healthiest] {
+            Some(rate_limit) if rate_limit.is_exhausted() => {
+                let (slot, reset) = rate_limits
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(slot, rate_limit)| Some((slot, rate_limit.as_ref()?.reset)))
+                    .min_by_key(|&(_, reset)| reset)
+                    .unwrap_or((healthiest, rate_limit.reset));
+                warn!(
+                    "all {} tokens are rate limited, falling back to token #{slot} resetting at {reset}",
+                    self.tokens.len()
+                );
+                slot
+            }
+            _ => healthiest,
+        }
+    }
+
+    fn observe(&self, slot: usize, headers: &HeaderMap) {
+        if let Some(rate_limit) = RateLimit::from_headers(headers) {
+            debug!(
+                "token #{slot} has {} / {} requests remaining",
+                rate_limit.remaining, rate_limit.limit
+            );
+            self.rate_limits.lock()[slot] = Some(rate_limit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(remaining: u32, reset: TimeDelta) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit", HeaderValue::from(5000));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from(remaining));
+        headers.insert(
+            "x-ratelimit-reset",
+            HeaderValue::from((Utc::now() + reset).timestamp()),
+        );
+        headers
+    }
+
+    #[test]
+    fn rotates_tokens() {
+        let pool = TokenPool::new(vec![
+            Secret::new(String::from("a")),
+            Secret::new(String::from("b")),
+        ]);
+        assert_eq!(pool.token().expose(), "a");
+
+        pool.observe(0, &headers(0, TimeDelta::minutes(30)));
+        assert_eq!(pool.token().expose(), "b");
+
+        pool.observe(1, &headers(0, TimeDelta::minutes(10)));
+        assert_eq!(pool.token().expose(), "b");
+
+        pool.observe(0, &headers(4999, TimeDelta::hours(1)));
+        assert_eq!(pool.token().expose(), "a");
+    }
+}
+
+#[cfg(feature = "github_app")]
+mod app {
+    use std::{
+        collections::HashMap,
+        fmt::{self, Debug},
+        sync::Arc,
+    };
+
+    use anyhow::Context as _;
+    use chrono::{DateTime, TimeDelta, Utc};
+    use jsonwebtoken::{Algorithm, EncodingKey, Header};
+    use parking_lot::Mutex;
+    use serde::{Deserialize, Serialize};
+    use tracing::{debug, info};
+
+    use crate::{
+        env::{self, Secret},
+        parse_env,
+        workflow::{
+            auth::AuthError,
+            client::{DEFAULT_API_VERSION, DEFAULT_BASE_URL, DEFAULT_USER_AGENT, github_headers},
+            error::error_for_status,
+        },
+    };
+
+    /// How long before expiry an installation token is refreshed.
+    const REFRESH_MARGIN: TimeDelta = TimeDelta::minutes(5);
+
+    #[derive(Serialize)]
+    struct Claims<'a> {
+        iat: i64,
+        exp: i64,
+        iss: &'a str,
+    }
+
+    #[derive(Deserialize)]
+    struct Installation {
+        id: u64,
+    }
+
+    #[derive(Deserialize)]
+    struct InstallationToken {
+        token: String,
+        expires_at: DateTime<Utc>,
+    }
+
+    #[derive(Debug)]
+    struct CachedToken {
+        token: Secret<String>,
+        expires_at: DateTime<Utc>,
+    }
+
+    /// A GitHub App that authenticates with installation tokens.
+    ///
+    /// Installation tokens are exchanged per owner with a JWT signed by the private key of the app, and are cached until shortly before they expire.
+    pub struct GitHubApp {
+        app_id: String,
+        key: EncodingKey,
+        installation_id: Option<u64>,
+        base_url: String,
+        client: reqwest::Client,
+        /// Locked per owner, so that exchanging a token for one owner does not block the others.
+        tokens: Mutex<HashMap<String, Arc<tokio::sync::Mutex<Option<CachedToken>>>>>,
+    }
+
+    impl GitHubApp {
+        /// Creates a [`GitHubApp`] from its app ID (or client ID) and its RSA private key in PEM format.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the private key is invalid, or the underlying client fails to build.
+        pub fn new<S>(app_id: S, private_key: &Secret<String>) -> anyhow::Result<Self>
+        where
+            S: Into<String>,
+        {
+            let key = EncodingKey::from_rsa_pem(private_key.expose().as_bytes())
+                .context("invalid GitHub App private key")?;
+            Ok(Self {
+                app_id: app_id.into(),
+                key,
+                installation_id: None,
+                base_url: String::from(DEFAULT_BASE_URL),
+                client: reqwest::Client::builder()
+                    .default_headers(github_headers(DEFAULT_API_VERSION)?)
+                    .user_agent(DEFAULT_USER_AGENT)
+                    .build()?,
+                tokens: Mutex::new(HashMap::new()),
+            })
+        }
+
+        /// Creates a [`GitHubApp`] from `GITHUB_APP_ID`, `GITHUB_APP_PRIVATE_KEY` (or `GITHUB_APP_PRIVATE_KEY_FILE`) and an optional `GITHUB_APP_INSTALLATION_ID`.
+        ///
+        /// Escaped line breaks (`\n`) in the private key are unescaped, so it can be put on a single line.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if any variable is missing or invalid.
+        pub fn from_env() -> anyhow::Result<Self> {
+            let app_id = env::var("GITHUB_APP_ID")?;
+            let private_key = Secret::from_env("GITHUB_APP_PRIVATE_KEY")?;
+            let private_key = Secret::new(private_key.expose().replace("\\n", "\n"));
+            let installation_id =
+                env::parse::optional(parse_env!("GITHUB_APP_INSTALLATION_ID" => u64))?;
+
+            let app = Self::new(app_id, &private_key)?;
+            Ok(match installation_id {
+                Some(installation_id) => app.installation_id(installation_id),
+                None => app,
+            })
+        }
+
+        /// Uses a fixed installation for all owners instead of looking it up per owner.
+        pub fn installation_id(self, installation_id: u64) -> Self {
+            Self {
+                installation_id: Some(installation_id),
+                ..self
+            }
+        }
+
+        /// Exchanges tokens through another HTTP client, like the one of a [`GitHubClient`](crate::workflow::client::GitHubClient) sharing its user agent and timeouts.
+        pub(crate) fn http(self, client: reqwest::Client) -> Self {
+            Self { client, ..self }
+        }
+
+        /// Uses another base URL of GitHub REST API.
+        pub fn base_url<S>(self, base_url: S) -> Self
+        where
+            S: Into<String>,
+        {
+            Self {
+                base_url: base_url.into().trim_end_matches('/').to_owned(),
+                ..self
+            }
+        }
+
+        /// Gets an installation token to access the resources of `owner`, refreshing it if it is about to expire.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the app is not installed for `owner`, which is an [`AuthError`], or the token exchange fails.
+        pub async fn installation_token(&self, owner: &str) -> anyhow::Result<Secret<String>> {
+            let slot = self
+                .tokens
+                .lock()
+                .entry(owner.to_owned())
+                .or_default()
+                .clone();
+            let mut cached = slot.lock().await;
+            if let Some(cached) = &*cached
+                && cached.expires_at - REFRESH_MARGIN > Utc::now()
+            {
+                return Ok(cached.token.clone());
+            }
+
+            debug!("refreshing installation token for {owner}…");
+            let jwt = self.jwt()?;
+            let installation_id = match self.installation_id {
+                Some(installation_id) => installation_id,
+                None => self.find_installation(&jwt, owner).await?,
+            };
+
+            let url = format!(
+                "{}/app/installations/{installation_id}/access_tokens",
+                self.base_url
+            );
+            let response = self
+                .client
+                .post(&url)
+                .bearer_auth(jwt.expose())
+                .send()
+                .await?;
+            let InstallationToken { token, expires_at } = error_for_status(response)
+                .await
+                .with_context(|| format!("failed to create installation token for {owner}"))?
+                .json()
+                .await?;
+            info!("refreshed installation token for {owner}, expiring at {expires_at}");
+
+            let token = Secret::new(token);
+            *cached = Some(CachedToken {
+                token: token.clone(),
+                expires_at,
+            });
+            Ok(token)
+        }
+
+        fn jwt(&self) -> anyhow::Result<Secret<String>> {
+            // Backdates the token in case of clock drift. GitHub accepts a lifetime of at most 10 minutes
+            let now = Utc::now().timestamp();
+            let claims = Claims {
+                iat: now - 60,
+                exp: now + 9 * 60,
+                iss: &self.app_id,
+            };
+            jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &self.key)
+                .map(Secret::new)
+                .context("failed to sign GitHub App JWT")
+        }
+
+        async fn find_installation(
+            &self,
+            jwt: &Secret<String>,
+            owner: &str,
+        ) -> anyhow::Result<u64> {
+            // Tries as an organization first, then as a user
+            for kind in ["orgs", "users"] {
+                let url = format!("{}/{kind}/{owner}/installation", self.base_url);
+                let response = self
+                    .client
+                    .get(&url)
+                    .bearer_auth(jwt.expose())
+                    .send()
+                    .await?;
+
+                if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    continue;
+                }
+
+                let installation: Installation = error_for_status(response)
+                    .await
+                    .with_context(|| format!("failed to find installation for {owner}"))?
+                    .json()
+                    .await?;
+                return Ok(installation.id);
+            }
+
+            Err(AuthError::NotInstalled {
+                app_id: self.app_id.clone(),
+                owner: owner.to_owned(),
+            }
+            .into())
+        }
+    }
+
+    impl Debug for GitHubApp {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("GitHubApp")
+                .field("app_id", &self.app_id)
+                .field("key", &"[REDACTED]")
+                .field("installation_id", &self.installation_id)
+                .field("base_url", &self.base_url)
+                .finish_non_exhaustive()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use wiremock::{
+            Mock, MockServer, ResponseTemplate,
+            matchers::{header, header_exists, method, path},
+        };
+
+        use super::*;
+        use crate::workflow::{auth::GitHubAuth, client::GitHubClient};
+
+        const PRIVATE_KEY: &str = include_str!("../../tests/fixtures/github_app_private_key.pem");
+
+        #[tokio::test]
+        async fn refreshes_installation_tokens() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/orgs/octocat/installation"))
+                .respond_with(ResponseTemplate::new(404))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/users/octocat/installation"))
+                .and(header_exists("authorization"))
+                .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id":42}"#))
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/app/installations/42/access_tokens"))
+                .respond_with(ResponseTemplate::new(201).set_body_string(format!(
+                    r#"{{"token":"ghs_installation","expires_at":"{}"}}"#,
+                    (Utc::now() + TimeDelta::hours(1)).to_rfc3339()
+                )))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let app = GitHubApp::new("1", &Secret::new(String::from(PRIVATE_KEY)))
+                .unwrap()
+                .base_url(server.uri());
+
+            for _ in 0..2 {
+                let token = app.installation_token("octocat").await.unwrap();
+                assert_eq!(token.expose(), "ghs_installation");
+            }
+
+            // Not installed for owners without installations
+            let err = app.installation_token("ghost").await.unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<AuthError>(),
+                Some(AuthError::NotInstalled { owner, .. }) if owner == "ghost"
+            ));
+        }
+
+        #[tokio::test]
+        async fn exchanges_tokens_through_the_client() {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/app/installations/42/access_tokens"))
+                .and(header("user-agent", "custom-agent"))
+                .respond_with(ResponseTemplate::new(201).set_body_string(format!(
+                    r#"{{"token":"ghs_installation","expires_at":"{}"}}"#,
+                    (Utc::now() + TimeDelta::hours(1)).to_rfc3339()
+                )))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let app = GitHubApp::new("1", &Secret::new(String::from(PRIVATE_KEY)))
+                .unwrap()
+                .installation_id(42);
+            let client = GitHubClient::builder(GitHubAuth::App(app))
+                .base_url(server.uri())
+                .user_agent("custom-agent")
+                .build()
+                .unwrap();
+            let token = client.auth().token("octocat").await.unwrap();
+            assert_eq!(token.expose(), "ghs_installation");
+        }
+    }
+}
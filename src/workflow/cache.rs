@@ -0,0 +1,328 @@
+//! Conditional requests to GitHub REST API, caching responses by their `ETag` and `Last-Modified` headers.
+//!
+//! A `304 Not Modified` response to a conditional request does not count against the primary rate limit, so polling unchanged resources is free.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+use reqwest::{
+    RequestBuilder,
+    header::{self, HeaderMap},
+};
+use serde::{Deserialize, Serialize};
+use sha2::Digest as _;
+use tracing::warn;
+
+use crate::workflow::pagination::next_link;
+
+/// The default maximum number of responses cached in memory.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// A cached response, with the validators to revalidate it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CachedResponse {
+    /// The credential the response was requested with. See: [`AuthToken::scope`](crate::workflow::auth::AuthToken::scope)
+    #[serde(default)]
+    scope: String,
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// The URL of the next page, required to follow the pagination of cached pages.
+    next: Option<String>,
+    body: String,
+}
+
+impl CachedResponse {
+    /// Creates a [`CachedResponse`] from the headers and the body of a response to a request with the credential identified by `scope`, or [`None`] if it has no validators.
+    pub(crate) fn new(scope: &str, url: &str, headers: &HeaderMap, body: String) -> Option<Self> {
+        let header = |name| Some(headers.get(name)?.to_str().ok()?.to_owned());
+        let etag = header(header::ETAG);
+        let last_modified = header(header::LAST_MODIFIED);
+        if etag.is_none() && last_modified.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            scope: scope.to_owned(),
+            url: url.to_owned(),
+            etag,
+            last_modified,
+            next: next_link(headers),
+            body,
+        })
+    }
+
+    /// Gets the URL of the next page, parsed from the `Link` header of the response.
+    pub(crate) fn next(&self) -> Option<&str> {
+        self.next.as_deref()
+    }
+
+    /// Gets the body of the response.
+    pub(crate) fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// Makes a request conditional on the response being modified.
+    pub(crate) fn revalidate(&self, builder: RequestBuilder) -> RequestBuilder {
+        let builder = match &self.etag {
+            Some(etag) => builder.header(header::IF_NONE_MATCH, etag),
+            None => builder,
+        };
+        match &self.last_modified {
+            Some(last_modified) => builder.header(header::IF_MODIFIED_SINCE, last_modified),
+            None => builder,
+        }
+    }
+}
+
+/// A cache of responses to GET requests of GitHub REST API, keyed by their URLs and the credentials they were requested with.
+///
+/// Responses are kept in memory, evicting the least recently used ones beyond the capacity, and optionally persisted to a directory so that they survive restarts.
+#[derive(Debug)]
+pub struct ResponseCache {
+    capacity: usize,
+    dir: Option<PathBuf>,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// The responses by their keys, with the generation they were last used in.
+    responses: HashMap<String, (u64, Arc<CachedResponse>)>,
+    /// The keys of the responses by the generation they were last used in, from the least recently used.
+    order: BTreeMap<u64, String>,
+    generation: u64,
+}
+
+impl Entries {
+    /// Marks the response at `key` as the most recently used, and gets it.
+    fn touch(&mut self, key: &str) -> Option<Arc<CachedResponse>> {
+        self.generation += 1;
+        let generation = self.generation;
+        let (used, cached) = self.responses.get_mut(key)?;
+        self.order.remove(used);
+        self.order.insert(generation, key.to_owned());
+        *used = generation;
+        Some(cached.clone())
+    }
+}
+
+impl ResponseCache {
+    /// Creates a [`ResponseCache`] in memory, holding at most [`DEFAULT_CAPACITY`] responses.
+    pub fn in_memory() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            dir: None,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// Creates a [`ResponseCache`] in memory, persisting the responses to `dir`. The directory is created if missing.
+    pub fn on_disk<P>(dir: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            dir: Some(dir.into()),
+            ..Self::in_memory()
+        }
+    }
+
+    /// Holds at most `capacity` responses in memory. The responses on disk are not limited.
+    pub fn capacity(self, capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ..self
+        }
+    }
+
+    /// Gets the directory the responses are persisted to, if any.
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    /// Removes all responses from memory and disk.
+    pub async fn clear(&self) {
+        *self.entries.lock() = Entries::default();
+        if let Some(dir) = &self.dir
+            && let Err(err) = tokio::fs::remove_dir_all(dir).await
+            && err.kind() != std::io::ErrorKind::NotFound
+        {
+            warn!("failed to clear response cache at {}: {err}", dir.display());
+        }
+    }
+
+    /// Gets the cached response to `url` requested with the credential identified by `scope`, loading it from disk if missing in memory.
+    pub(crate) async fn get(&self, scope: &str, url: &str) -> Option<Arc<CachedResponse>> {
+        let key = key(scope, url);
+        if let Some(cached) = self.entries.lock().touch(&key) {
+            return Some(cached);
+        }
+
+        let path = self.path(&key)?;
+        let content = tokio::fs::read(&path).await.ok()?;
+        match serde_json::from_slice::<CachedResponse>(&content) {
+            // Guards against hash collisions
+            Ok(cached) if cached.scope == scope && cached.url == url => {
+                let cached = Arc::new(cached);
+                self.remember(key, cached.clone());
+                Some(cached)
+            }
+            Ok(_) => None,
+            Err(err) => {
+                warn!(
+                    "ignoring corrupted cached response at {}: {err}",
+                    path.display()
+                );
+                None
+            }
+        }
+    }
+
+    /// Stores a response, persisting it to disk if configured.
+    ///
+    /// The response is written to a temporary file first and renamed, so that concurrent readers never see a partial response.
+    pub(crate) async fn put(&self, cached: CachedResponse) {
+        let key = key(&cached.scope, &cached.url);
+        let cached = Arc::new(cached);
+        let generation = self.remember(key.clone(), cached.clone());
+
+        let Some(path) = self.path(&key) else {
+            return;
+        };
+        let temp = path.with_extension(format!("{}.{generation}.tmp", std::process::id()));
+        let result = async {
+            if let Some(dir) = path.parent() {
+                tokio::fs::create_dir_all(dir).await?;
+            }
+            tokio::fs::write(&temp, serde_json::to_vec(&*cached)?).await?;
+            tokio::fs::rename(&temp, &path).await?;
+            anyhow::Ok(())
+        };
+        if let Err(err) = result.await {
+            warn!(
+                "failed to persist cached response to {}: {err:#}",
+                path.display()
+            );
+            // Best effort, as persisting has already failed
+            tokio::fs::remove_file(&temp).await.ok();
+        }
+    }
+
+    /// Stores a response in memory as the most recently used, evicting the least recently used ones beyond the capacity. Returns its generation.
+    fn remember(&self, key: String, cached: Arc<CachedResponse>) -> u64 {
+        let mut entries = self.entries.lock();
+        entries.generation += 1;
+        let generation = entries.generation;
+        if let Some((used, _)) = entries.responses.insert(key.clone(), (generation, cached)) {
+            entries.order.remove(&used);
+        }
+        entries.order.insert(generation, key);
+
+        while entries.responses.len() > self.capacity {
+            let Some((_, oldest)) = entries.order.pop_first() else {
+                break;
+            };
+            entries.responses.remove(&oldest);
+        }
+        generation
+    }
+
+    fn path(&self, key: &str) -> Option<PathBuf> {
+        let hash = hex::encode(sha2::Sha256::digest(key.as_bytes()));
+        Some(self.dir.as_ref()?.join(format!("{hash}.json")))
+    }
+}
+
+/// Keys a response by the credential identified by `scope` and its URL.
+fn key(scope: &str, url: &str) -> String {
+    format!("{scope} {url}")
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{header, method, path},
+    };
+
+    use super::*;
+    use crate::workflow::client::test_builder;
+
+    #[tokio::test]
+    async fn revalidates_cached_responses() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello"))
+            .and(header("if-none-match", r#""v1""#))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(2)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", r#""v1""#)
+                    .set_body_string(r#"{"id":1}"#),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = std::env::temp_dir().join(format!("api-framework-cache-{}", std::process::id()));
+        let client = test_builder(&server)
+            .cache(ResponseCache::on_disk(&dir))
+            .build()
+            .unwrap();
+        let url = client.url("/repos/octocat/hello");
+
+        for _ in 0..2 {
+            let repo: serde_json::Value = client.get_json(&url).await.unwrap();
+            assert_eq!(repo["id"], 1);
+        }
+
+        // A fresh client loads the cached response from disk
+        let client = test_builder(&server)
+            .cache(ResponseCache::on_disk(&dir))
+            .build()
+            .unwrap();
+        let repo: serde_json::Value = client.get_json(&url).await.unwrap();
+        assert_eq!(repo["id"], 1);
+
+        client.cache().unwrap().clear().await;
+        assert!(!dir.exists());
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used_responses() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ETAG, r#""v1""#.parse().unwrap());
+        let response = |scope: &str, url: &str| {
+            CachedResponse::new(scope, url, &headers, String::from("{}")).unwrap()
+        };
+
+        let cache = ResponseCache::in_memory().capacity(2);
+        cache.put(response("a", "/first")).await;
+        cache.put(response("a", "/second")).await;
+        assert!(cache.get("a", "/first").await.is_some());
+        cache.put(response("a", "/third")).await;
+
+        assert!(cache.get("a", "/first").await.is_some());
+        assert!(cache.get("a", "/second").await.is_none());
+        assert!(cache.get("a", "/third").await.is_some());
+        // Responses are never shared across credentials
+        assert!(cache.get("b", "/first").await.is_none());
+    }
+}
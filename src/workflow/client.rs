@@ -0,0 +1,871 @@
+//! A reusable client for GitHub REST API.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use futures::{Stream, TryStreamExt as _, stream};
+use reqwest::{
+    Method, RequestBuilder, Response, ResponseBuilderExt as _, StatusCode, Url,
+    header::{self, HeaderMap, HeaderValue},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Serialize, de::DeserializeOwned};
+use tracing::{debug, info, warn};
+
+use crate::{
+    env::{self, parse::optional},
+    framework::deadline,
+    parse_env, static_lazy_lock,
+    workflow::{
+        auth::{AuthToken, GitHubAuth},
+        cache::{CachedResponse, ResponseCache},
+        error::error_for_status,
+        pagination::{Page, next_link},
+        rate_limit::{RateLimitStatus, RateLimitTracker, RateLimited},
+    },
+};
+
+/// The default base URL of GitHub REST API.
+pub const DEFAULT_BASE_URL: &str = "https://api.github.com";
+/// The default user agent sent to GitHub REST API.
+pub const DEFAULT_USER_AGENT: &str = "KessokuTeaTime-API/1.0";
+/// The default version of GitHub REST API.
+pub const DEFAULT_API_VERSION: &str = "2022-11-28";
+/// The default page size for paginated list endpoints, which is the maximum allowed.
+pub const DEFAULT_PER_PAGE: u8 = 100;
+/// The default maximum duration to wait for a rate limit to reset before giving up.
+pub const DEFAULT_MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(15 * 60);
+
+/// How many times a request is resent after waiting for a rate limit to reset.
+const MAX_RATE_LIMIT_RESENDS: u8 = 3;
+
+static_lazy_lock! {
+    /// The shared client configured from environment variables, used by the transactions that do not take a client.
+    ///
+    /// See: [`GitHubClient::from_env`]
+    pub GITHUB_CLIENT: GitHubClient = GitHubClient::from_env().unwrap_or_else(|e| panic!("{e:#}"));
+}
+
+/// A client for GitHub REST API.
+///
+/// The client holds a connection pool and should be constructed once and reused. Cloning is cheap and shares the pool.
+#[derive(Debug, Clone)]
+pub struct GitHubClient {
+    http: reqwest::Client,
+    base_url: Arc<str>,
+    per_page: u8,
+    auth: Arc<GitHubAuth>,
+    max_rate_limit_wait: Duration,
+    rate_limits: Arc<RateLimitTracker>,
+    cache: Option<Arc<ResponseCache>>,
+}
+
+impl GitHubClient {
+    /// Creates a [`GitHubClientBuilder`] authenticating with `auth`.
+    pub fn builder(auth: GitHubAuth) -> GitHubClientBuilder {
+        GitHubClientBuilder {
+            auth,
+            base_url: String::from(DEFAULT_BASE_URL),
+            user_agent: String::from(DEFAULT_USER_AGENT),
+            api_version: String::from(DEFAULT_API_VERSION),
+            per_page: DEFAULT_PER_PAGE,
+            timeout: None,
+            connect_timeout: None,
+            max_rate_limit_wait: DEFAULT_MAX_RATE_LIMIT_WAIT,
+            cache: Some(ResponseCache::in_memory()),
+        }
+    }
+
+    /// Creates a [`GitHubClient`] from environment variables.
+    ///
+    /// The authentication is selected by [`GitHubAuth::from_env`]. The base URL is `GITHUB_API_URL` if set, which is also provided to GitHub Actions runners, or derived from the server URL at `GITHUB_ENTERPRISE_URL` for GitHub Enterprise Server. `GITHUB_TIMEOUT` optionally limits the duration of each request, and `GITHUB_MAX_RATE_LIMIT_WAIT` the duration to wait for a rate limit to reset. Responses are cached in memory, and also persisted to `GITHUB_CACHE_DIR` if set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any variable is missing or invalid, or the client fails to build.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let mut builder = Self::builder(GitHubAuth::from_env()?);
+        if let Ok(base_url) = env::var("GITHUB_API_URL") {
+            builder = builder.base_url(base_url);
+        } else if let Ok(server_url) = env::var("GITHUB_ENTERPRISE_URL") {
+            builder = builder.enterprise_url(&server_url);
+        }
+        if let Some(timeout) = optional(parse_env!("GITHUB_TIMEOUT" => duration))? {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(max_wait) = optional(parse_env!("GITHUB_MAX_RATE_LIMIT_WAIT" => duration))? {
+            builder = builder.max_rate_limit_wait(max_wait);
+        }
+        if let Ok(dir) = env::var("GITHUB_CACHE_DIR") {
+            builder = builder.cache(ResponseCache::on_disk(dir));
+        }
+        builder.build()
+    }
+
+    /// Gets the base URL of GitHub REST API, without a trailing slash.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Gets the authentication of this client.
+    pub fn auth(&self) -> &GitHubAuth {
+        &self.auth
+    }
+
+    /// Takes a snapshot of the rate limits observed by this client, for monitoring.
+    ///
+    /// The rate limits of each token in a [`TokenPool`](crate::workflow::auth::TokenPool) are also available through [`TokenPool::rate_limits`](crate::workflow::auth::TokenPool::rate_limits).
+    pub fn rate_limit_status(&self) -> RateLimitStatus {
+        self.rate_limits.status()
+    }
+
+    /// Gets the cache of responses of this client, if enabled.
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_deref()
+    }
+
+    /// Joins a path like `/repos/{owner}/{repo}` to the base URL.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path.trim_start_matches('/'))
+    }
+
+    /// Streams the items of a paginated list endpoint at `url`, following the `Link: rel="next"` headers until the last page.
+    ///
+    /// The `per_page` query of the first request is set to the page size of this client, unless already present.
+    pub fn paginate<P>(
+        &self,
+        url: &str,
+    ) -> impl Stream<Item = anyhow::Result<P::Item>> + Send + use<'_, P>
+    where
+        P: Page + DeserializeOwned,
+        P::Item: Send,
+    {
+        // An invalid URL is kept as-is, and fails when sent
+        let first = Url::parse(url).map_or_else(
+            |_| url.to_owned(),
+            |mut url| {
+                if !url.query_pairs().any(|(key, _)| key == "per_page") {
+                    url.query_pairs_mut()
+                        .append_pair("per_page", &self.per_page.to_string());
+                }
+                url.into()
+            },
+        );
+
+        // The owner is only known from the first URL, as the next links are like `/repositories/{id}/...`, so all pages are authenticated for it. A token is picked per page, so that a pool rotates mid-listing
+        let owner = owner_of(&first).unwrap_or_default().to_owned();
+        stream::try_unfold(Some(first), move |url| {
+            let owner = owner.clone();
+            async move {
+                let Some(url) = url else {
+                    return Ok(None);
+                };
+                let token = self.auth.token(&owner).await?;
+                debug!("fetching page {url}…");
+
+                let (body, next) = self.get_body(&url, token).await?;
+                let page: P = serde_json::from_str(&body)?;
+                anyhow::Ok(Some((page.into_items(), next)))
+            }
+        })
+        .map_ok(|items| stream::iter(items.into_iter().map(anyhow::Ok)))
+        .try_flatten()
+    }
+
+    /// Sends a GET request to `url` and deserializes the JSON response.
+    ///
+    /// The request is conditional if a response to `url` is cached, which is reused if not modified.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails, the response has an error status, or the response fails to deserialize. An error status is reported as a [`GitHubError`](crate::workflow::error::GitHubError).
+    pub async fn get_json<T>(&self, url: &str) -> anyhow::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let (body, _) = self.get_body(url, self.token_for(url).await?).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Sends a request with a JSON body to `url` and deserializes the JSON response, like to create or update a resource.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails, the response has an error status, or the response fails to deserialize. An error status is reported as a [`GitHubError`](crate::workflow::error::GitHubError).
+    pub async fn send_json<B, T>(&self, method: Method, url: &str, body: &B) -> anyhow::Result<T>
+    where
+        B: Serialize + Sync + ?Sized,
+        T: DeserializeOwned,
+    {
+        Ok(self.send_body(method, url, body).await?.json().await?)
+    }
+
+    /// Sends a request with a JSON body to `url`, ignoring the response, like to trigger an action answered by `204 No Content`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails, or the response has an error status, reported as a [`GitHubError`](crate::workflow::error::GitHubError).
+    pub async fn send_body<B>(
+        &self,
+        method: Method,
+        url: &str,
+        body: &B,
+    ) -> anyhow::Result<Response>
+    where
+        B: Serialize + Sync + ?Sized,
+    {
+        let response = self
+            .request(method, url)
+            .await?
+            .map(|builder| builder.json(body))
+            .send()
+            .await?;
+        Ok(error_for_status(response).await?)
+    }
+
+    /// Sends a GET request to `url` authenticated by `token`, revalidating the cached response if any. Returns the body and the URL of the next page.
+    async fn get_body(
+        &self,
+        url: &str,
+        token: AuthToken,
+    ) -> anyhow::Result<(String, Option<String>)> {
+        let cache = self.cache.as_deref().map(|cache| (cache, token.scope()));
+        let cached = match &cache {
+            Some((cache, scope)) => cache.get(scope, url).await,
+            None => None,
+        };
+
+        let mut request = self.request_with(Method::GET, url, token);
+        if let Some(cached) = &cached {
+            request = request.map(|builder| cached.revalidate(builder));
+        }
+        let response = request.send().await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let Some(cached) = cached
+        {
+            debug!("{url} is not modified, reusing the cached response");
+            return Ok((cached.body().to_owned(), cached.next().map(str::to_owned)));
+        }
+
+        let response = error_for_status(response).await?;
+        let headers = response.headers().clone();
+        let body = response.text().await?;
+        if let Some((cache, scope)) = &cache
+            && let Some(cached) = CachedResponse::new(scope, url, &headers, body.clone())
+        {
+            cache.put(cached).await;
+        }
+        Ok((body, next_link(&headers)))
+    }
+
+    /// Builds a GET request to `url`.
+    ///
+    /// # Errors
+    ///
+    /// See: [`Self::request`]
+    pub async fn get(&self, url: &str) -> anyhow::Result<GitHubRequest<'_>> {
+        self.request(Method::GET, url).await
+    }
+
+    /// Builds a request to `url`, authenticated for the owner of the requested repository.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a token cannot be obtained for the owner.
+    pub async fn request(&self, method: Method, url: &str) -> anyhow::Result<GitHubRequest<'_>> {
+        Ok(self.request_with(method, url, self.token_for(url).await?))
+    }
+
+    /// Gets a token for the owner of the repository requested at `url`.
+    async fn token_for(&self, url: &str) -> anyhow::Result<AuthToken> {
+        self.auth.token(owner_of(url).unwrap_or_default()).await
+    }
+
+    /// Builds a request to `url` authenticated by `token`.
+    fn request_with(&self, method: Method, url: &str, token: AuthToken) -> GitHubRequest<'_> {
+        let builder = self.http.request(method, url).bearer_auth(token.expose());
+        GitHubRequest {
+            client: self,
+            builder,
+            token,
+        }
+    }
+
+    /// Waits until a rate limit resets at `until`, unless that exceeds the maximum wait or the deadline of the current business.
+    ///
+    /// Returns whether it has waited.
+    async fn wait_for_rate_limit(&self, until: DateTime<Utc>) -> bool {
+        let wait = (until - Utc::now()).to_std().unwrap_or_default();
+        let bound = match deadline::remaining() {
+            Some(remaining) => remaining.min(self.max_rate_limit_wait),
+            None => self.max_rate_limit_wait,
+        };
+
+        if wait > bound {
+            warn!(
+                "not waiting {wait:?} for the rate limit to reset, exceeding the bound of {bound:?}"
+            );
+            false
+        } else {
+            info!("waiting {wait:?} for the rate limit to reset…");
+            tokio::time::sleep(wait).await;
+            true
+        }
+    }
+}
+
+/// A builder of [`GitHubClient`].
+#[derive(Debug)]
+pub struct GitHubClientBuilder {
+    auth: GitHubAuth,
+    base_url: String,
+    user_agent: String,
+    api_version: String,
+    per_page: u8,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    max_rate_limit_wait: Duration,
+    cache: Option<ResponseCache>,
+}
+
+impl GitHubClientBuilder {
+    /// Uses another base URL of GitHub REST API, like a local mock server. Defaults to [`DEFAULT_BASE_URL`].
+    ///
+    /// The URL is used as-is. For GitHub Enterprise Server, see [`Self::enterprise_url`].
+    pub fn base_url<S>(mut self, base_url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Uses the REST API of a GitHub Enterprise Server instance at `server_url`, like `https://github.example.com`.
+    ///
+    /// See: [`enterprise_base_url`]
+    pub fn enterprise_url(self, server_url: &str) -> Self {
+        self.base_url(enterprise_base_url(server_url))
+    }
+
+    /// Uses another user agent. Defaults to [`DEFAULT_USER_AGENT`].
+    pub fn user_agent<S>(mut self, user_agent: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Uses another version of GitHub REST API. Defaults to [`DEFAULT_API_VERSION`].
+    pub fn api_version<S>(mut self, api_version: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.api_version = api_version.into();
+        self
+    }
+
+    /// Uses another page size for paginated list endpoints, at most 100. Defaults to [`DEFAULT_PER_PAGE`].
+    pub const fn per_page(mut self, per_page: u8) -> Self {
+        self.per_page = per_page;
+        self
+    }
+
+    /// Limits the duration of each request, from connecting until the response body is read.
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Limits the duration of connecting.
+    pub const fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Limits the duration to wait for a rate limit to reset, which is also bounded by the deadline of the current business. Defaults to [`DEFAULT_MAX_RATE_LIMIT_WAIT`].
+    ///
+    /// See: [`deadline`](crate::framework::deadline)
+    pub const fn max_rate_limit_wait(mut self, max_rate_limit_wait: Duration) -> Self {
+        self.max_rate_limit_wait = max_rate_limit_wait;
+        self
+    }
+
+    /// Uses another cache of responses. Defaults to [`ResponseCache::in_memory`].
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Disables caching responses, so that every request is unconditional.
+    pub fn no_cache(mut self) -> Self {
+        self.cache = None;
+        self
+    }
+
+    /// Builds the [`GitHubClient`]. A GitHub App authentication is also pointed to the base URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the base URL is invalid, the user agent or the API version is not a valid header value, or the underlying client fails to build.
+    pub fn build(self) -> anyhow::Result<GitHubClient> {
+        let base_url = self.base_url.trim_end_matches('/');
+        Url::parse(base_url).with_context(|| format!("invalid GitHub API base URL {base_url}"))?;
+
+        let mut http = reqwest::Client::builder()
+            .default_headers(github_headers(&self.api_version)?)
+            .user_agent(self.user_agent);
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            http = http.connect_timeout(connect_timeout);
+        }
+        let http = http.build()?;
+
+        // The app exchanges tokens with the same user agent and timeouts
+        let auth = match self.auth {
+            #[cfg(feature = "github_app")]
+            GitHubAuth::App(app) => GitHubAuth::App(app.base_url(base_url).http(http.clone())),
+            auth => auth,
+        };
+
+        Ok(GitHubClient {
+            http,
+            base_url: base_url.into(),
+            per_page: self.per_page.clamp(1, 100),
+            auth: Arc::new(auth),
+            max_rate_limit_wait: self.max_rate_limit_wait,
+            rate_limits: Arc::default(),
+            cache: self.cache.map(Arc::new),
+        })
+    }
+}
+
+/// An authenticated request to GitHub REST API, built by [`GitHubClient`].
+///
+/// The rate limit of the token is recorded from the response once sent. See: [`Self::send`]
+#[derive(Debug)]
+pub struct GitHubRequest<'a> {
+    client: &'a GitHubClient,
+    builder: RequestBuilder,
+    token: AuthToken,
+}
+
+impl GitHubRequest<'_> {
+    /// Modifies the underlying [`RequestBuilder`], like adding queries or a body.
+    pub fn map<F>(self, f: F) -> Self
+    where
+        F: FnOnce(RequestBuilder) -> RequestBuilder,
+    {
+        Self {
+            builder: f(self.builder),
+            ..self
+        }
+    }
+
+    /// Sends the request.
+    ///
+    /// If the response hits a rate limit, the request is resent once the rate limit resets, as long as waiting is within [`GitHubClientBuilder::max_rate_limit_wait`] and the deadline of the current business. Otherwise, the rate limited response is returned. A primary rate limit of a token in a [`TokenPool`](crate::workflow::auth::TokenPool) is not waited for if other tokens remain, and the request is resent with the next token instead.
+    ///
+    /// After hitting a secondary rate limit, all requests of the client are paused until it resets. If the pause cannot be waited for, a `429 Too Many Requests` response is returned without sending the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails to build or send. See: [`RequestBuilder::send`]
+    pub async fn send(self) -> reqwest::Result<Response> {
+        let Self {
+            client,
+            builder,
+            mut token,
+        } = self;
+        let (http, request) = builder.build_split();
+        let mut request = request?;
+
+        let mut resends: u8 = 0;
+        let mut rotations: usize = 0;
+        loop {
+            if let Some(until) = client.rate_limits.paused_until()
+                && !client.wait_for_rate_limit(until).await
+            {
+                return Ok(paused_response(request.url().clone(), until));
+            }
+
+            // Bodies of streams cannot be resent
+            let resend = request.try_clone();
+            let response = http.execute(request).await?;
+            client.auth.observe(&token, response.headers());
+            client.rate_limits.observe(response.headers());
+
+            let (response, rate_limited) = detect_rate_limit(response).await?;
+            let Some(rate_limited) = rate_limited else {
+                return Ok(response);
+            };
+            warn!("{rate_limited} at {}", response.url());
+            client.rate_limits.hit(&rate_limited);
+
+            if let (RateLimited::Primary(_), GitHubAuth::Pool(pool)) =
+                (&rate_limited, &*client.auth)
+                && !pool.is_exhausted()
+            {
+                // Resends with the next token, which differs as the exhausted one has just been observed
+                let next = pool.token();
+                match (resend, next.authorization()) {
+                    (Some(mut resend), Some(authorization))
+                        if rotations < pool.len() && next.slot() != token.slot() =>
+                    {
+                        debug!("resending with the next token in the pool…");
+                        resend
+                            .headers_mut()
+                            .insert(header::AUTHORIZATION, authorization);
+                        rotations += 1;
+                        request = resend;
+                        token = next;
+                        continue;
+                    }
+                    _ => return Ok(response),
+                }
+            }
+            match resend {
+                Some(resend)
+                    if resends < MAX_RATE_LIMIT_RESENDS
+                        && client.wait_for_rate_limit(rate_limited.until()).await =>
+                {
+                    resends += 1;
+                    request = resend;
+                }
+                _ => return Ok(response),
+            }
+        }
+    }
+}
+
+/// Detects the rate limit hit by a response. The body of a `403 Forbidden` response that is not rate limited by its headers is read for the message of a secondary rate limit, and the response is rebuilt around the body.
+///
+/// See: [`RateLimited::from_response_body`]
+async fn detect_rate_limit(response: Response) -> reqwest::Result<(Response, Option<RateLimited>)> {
+    let status = response.status();
+    if let Some(rate_limited) = RateLimited::from_response(status, response.headers()) {
+        return Ok((response, Some(rate_limited)));
+    }
+    if status != StatusCode::FORBIDDEN {
+        return Ok((response, None));
+    }
+
+    let version = response.version();
+    let url = response.url().clone();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?;
+    let rate_limited =
+        RateLimited::from_response_body(status, &headers, &String::from_utf8_lossy(&body));
+
+    let mut rebuilt = http::Response::builder()
+        .status(status)
+        .version(version)
+        .url(url)
+        .body(body)
+        .expect("the status and the URL are valid");
+    *rebuilt.headers_mut() = headers;
+    Ok((rebuilt.into(), rate_limited))
+}
+
+/// Builds the `429 Too Many Requests` response to a request not sent while the client is paused until `until`, as if GitHub responded.
+fn paused_response(url: Url, until: DateTime<Utc>) -> Response {
+    let retry_after = (until - Utc::now()).num_seconds().max(0) + 1;
+    let body =
+        format!(r#"{{"message":"requests are paused for a secondary rate limit until {until}"}}"#);
+    http::Response::builder()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .url(url)
+        .header(header::RETRY_AFTER, retry_after)
+        .body(body)
+        .expect("the status, the URL and the header are valid")
+        .into()
+}
+
+/// Derives the base URL of GitHub REST API from the URL of a GitHub Enterprise Server instance, by appending the `/api/v3` prefix if missing.
+///
+/// # Examples
+///
+/// ```rust
+/// # use api_framework::workflow::client::enterprise_base_url;
+/// assert_eq!(enterprise_base_url("https://github.example.com/"), "https://github.example.com/api/v3");
+/// assert_eq!(enterprise_base_url("https://github.example.com/api/v3"), "https://github.example.com/api/v3");
+/// ```
+pub fn enterprise_base_url(server_url: &str) -> String {
+    let server_url = server_url.trim_end_matches('/');
+    if server_url.ends_with("/api/v3") {
+        server_url.to_owned()
+    } else {
+        format!("{server_url}/api/v3")
+    }
+}
+
+/// Builds the default headers required by GitHub REST API, except the user agent.
+///
+/// # Errors
+///
+/// Returns an error if the API version is not a valid header value.
+pub(crate) fn github_headers(api_version: &str) -> anyhow::Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::ACCEPT,
+        HeaderValue::from_static("application/vnd.github+json"),
+    );
+    headers.insert("X-GitHub-Api-Version", HeaderValue::from_str(api_version)?);
+    Ok(headers)
+}
+
+/// Adds the headers required by GitHub REST API to a request not built by [`GitHubClient`].
+pub(crate) fn with_github_headers(builder: RequestBuilder) -> RequestBuilder {
+    builder
+        .header(header::ACCEPT, "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", DEFAULT_API_VERSION)
+        .header(header::USER_AGENT, DEFAULT_USER_AGENT)
+}
+
+/// Extracts the owner from a URL like `https://api.github.com/repos/{owner}/{repo}/...`.
+fn owner_of(url: &str) -> Option<&str> {
+    let (_, path) = url.split_once("/repos/")?;
+    path.split('/').next().filter(|owner| !owner.is_empty())
+}
+
+/// Builds a client against a mock server, authenticating with a dummy token.
+#[cfg(test)]
+pub(crate) fn test_builder(server: &wiremock::MockServer) -> GitHubClientBuilder {
+    GitHubClient::builder(GitHubAuth::Token(env::Secret::new(String::from("token"))))
+        .base_url(server.uri())
+}
+
+/// Builds a client against a mock server, authenticating with a dummy token.
+#[cfg(test)]
+pub(crate) fn test_client(server: &wiremock::MockServer) -> GitHubClient {
+    test_builder(server).build().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use reqwest::StatusCode;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{header, method, path, query_param},
+    };
+
+    use super::*;
+    use crate::{
+        framework::deadline::with_deadline,
+        workflow::{
+            artifact::{Artifact, Artifacts},
+            auth::TokenPool,
+        },
+    };
+
+    #[tokio::test]
+    async fn waits_for_rate_limits() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rate_limited"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "1"))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rate_limited"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let url = client.url("/rate_limited");
+
+        let start = Instant::now();
+        let response = client.get(&url).await.unwrap().send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(start.elapsed() >= Duration::from_millis(500));
+        assert_eq!(client.rate_limit_status().hits, 1);
+
+        // The deadline is too close to wait for the rate limit
+        Mock::given(method("GET"))
+            .and(path("/rate_limited"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "60"))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        let response = with_deadline(Instant::now() + Duration::from_secs(5), async {
+            client.get(&url).await.unwrap().send().await.unwrap()
+        })
+        .await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(client.rate_limit_status().paused_until.is_some());
+
+        // The pause is too long to wait for, so the request is not sent
+        let received = server.received_requests().await.unwrap().len();
+        let response = with_deadline(Instant::now() + Duration::from_secs(5), async {
+            client.get(&url).await.unwrap().send().await.unwrap()
+        })
+        .await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.url().as_str(), url);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+        assert_eq!(server.received_requests().await.unwrap().len(), received);
+    }
+
+    #[tokio::test]
+    async fn rotates_pool_tokens_on_primary_rate_limits() {
+        let rate_limit = |status: u16, remaining: u32| {
+            ResponseTemplate::new(status)
+                .insert_header("x-ratelimit-limit", "5000")
+                .insert_header("x-ratelimit-remaining", remaining.to_string().as_str())
+                .insert_header(
+                    "x-ratelimit-reset",
+                    (Utc::now() + chrono::TimeDelta::hours(1))
+                        .timestamp()
+                        .to_string()
+                        .as_str(),
+                )
+        };
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello/actions/runs/1"))
+            .and(header("authorization", "Bearer a"))
+            .respond_with(rate_limit(403, 0))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello/actions/runs/1"))
+            .and(header("authorization", "Bearer b"))
+            .respond_with(rate_limit(200, 1).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let artifact =
+            |id: u64| Artifact::new(id, "site", format!("{}/artifacts/{id}", server.uri()));
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello/actions/artifacts"))
+            .and(header("authorization", "Bearer c"))
+            .respond_with(
+                rate_limit(200, 0)
+                    .insert_header(
+                        "link",
+                        format!(
+                            r#"<{}/repositories/1/actions/artifacts?page=2>; rel="next""#,
+                            server.uri()
+                        ),
+                    )
+                    .set_body_json(Artifacts::new(vec![artifact(1)])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/1/actions/artifacts"))
+            .and(header("authorization", "Bearer b"))
+            .respond_with(rate_limit(200, 0).set_body_json(Artifacts::new(vec![artifact(2)])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let pool = TokenPool::new(
+            ["a", "b", "c"]
+                .map(|token| env::Secret::new(token.to_owned()))
+                .into(),
+        );
+        let client = GitHubClient::builder(GitHubAuth::Pool(pool))
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+
+        // Token a is exhausted, so the request is resent with token b
+        client
+            .get_json::<serde_json::Value>(&client.url("/repos/octocat/hello/actions/runs/1"))
+            .await
+            .unwrap();
+
+        // Token c, unused so far, is exhausted by the first page, so the second one is fetched with token b
+        let artifacts: Vec<Artifact> = client
+            .paginate::<Artifacts>(&client.url("/repos/octocat/hello/actions/artifacts"))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(artifacts.len(), 2);
+    }
+
+    #[cfg(feature = "github_app")]
+    #[tokio::test]
+    async fn paginates_with_tokens_for_the_owner_of_the_first_page() {
+        use crate::{env::Secret, workflow::auth::GitHubApp};
+
+        const PRIVATE_KEY: &str = include_str!("../../tests/fixtures/github_app_private_key.pem");
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/orgs/octocat/installation"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id":42}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/app/installations/42/access_tokens"))
+            .respond_with(ResponseTemplate::new(201).set_body_string(format!(
+                r#"{{"token":"ghs_installation","expires_at":"{}"}}"#,
+                (Utc::now() + chrono::TimeDelta::hours(1)).to_rfc3339()
+            )))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let artifact =
+            |id: u64| Artifact::new(id, "site", format!("{}/artifacts/{id}", server.uri()));
+        Mock::given(method("GET"))
+            .and(path("/repos/octocat/hello/actions/artifacts"))
+            .and(header("authorization", "Bearer ghs_installation"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header(
+                        "link",
+                        format!(
+                            r#"<{}/repositories/1/actions/artifacts?per_page=1&page=2>; rel="next""#,
+                            server.uri()
+                        ),
+                    )
+                    .set_body_json(Artifacts::new(vec![artifact(1)])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/1/actions/artifacts"))
+            .and(query_param("page", "2"))
+            .and(header("authorization", "Bearer ghs_installation"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(Artifacts::new(vec![artifact(2)])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let app = GitHubApp::new("1", &Secret::new(String::from(PRIVATE_KEY))).unwrap();
+        let client = GitHubClient::builder(GitHubAuth::App(app))
+            .base_url(server.uri())
+            .build()
+            .unwrap();
+        let artifacts: Vec<Artifact> = client
+            .paginate::<Artifacts>(&client.url("/repos/octocat/hello/actions/artifacts"))
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            artifacts
+                .iter()
+                .map(|artifact| artifact.id)
+                .collect::<Vec<_>>(),
+            [1, 2]
+        );
+    }
+}
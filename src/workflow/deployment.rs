@@ -0,0 +1,187 @@
+//! Deployments from GitHub REST API, shown on the environment pages of repositories.
+
+use std::fmt::{self, Display};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::workflow::WorkflowRun;
+
+/// Represents a deployment from GitHub REST API.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Deployment {
+    /// The unique identifier of the deployment.
+    pub id: u64,
+    /// The SHA of the deployed commit.
+    pub sha: String,
+    /// The deployed ref, like a branch or a SHA.
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    /// The name of the environment, like `production`.
+    pub environment: String,
+    /// The description of the deployment.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The time when the deployment was created.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl Display for Deployment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} of {} to {}", self.id, self.sha, self.environment)
+    }
+}
+
+/// A deployment to create.
+///
+/// Unlike the defaults of GitHub, the deployment is neither merged with the default branch nor blocked by commit statuses, since the deployed commit is already built.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct NewDeployment {
+    /// The ref to deploy, like a branch or a SHA.
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    /// The name of the environment, like `production`.
+    pub environment: String,
+    /// The description of the deployment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Whether to merge the default branch into the ref first.
+    pub auto_merge: bool,
+    /// The contexts of commit statuses required to succeed before deploying.
+    pub required_contexts: Vec<String>,
+    /// Whether the environment is removed once no longer deployed, like for previews.
+    pub transient_environment: bool,
+    /// Whether the environment is used by end users.
+    pub production_environment: bool,
+}
+
+impl NewDeployment {
+    /// Creates a [`NewDeployment`] of a commit to an environment.
+    pub fn new<S, E>(sha: S, environment: E) -> Self
+    where
+        S: Into<String>,
+        E: Into<String>,
+    {
+        let environment = environment.into();
+        Self {
+            git_ref: sha.into(),
+            production_environment: environment == "production",
+            environment,
+            description: None,
+            auto_merge: false,
+            required_contexts: Vec::new(),
+            transient_environment: false,
+        }
+    }
+
+    /// Creates a [`NewDeployment`] of the head commit of a workflow run to an environment, like the workflow run of an artifact.
+    pub fn from_run<E>(run: &WorkflowRun, environment: E) -> Self
+    where
+        E: Into<String>,
+    {
+        Self::new(&run.head_sha, environment).description(format!("Workflow run {run}"))
+    }
+
+    /// Sets the description.
+    pub fn description<S>(mut self, description: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Marks the environment as transient, removed once no longer deployed.
+    pub const fn transient(mut self, transient_environment: bool) -> Self {
+        self.transient_environment = transient_environment;
+        self
+    }
+
+    /// Marks the environment as used by end users. Defaults to whether the environment is named `production`.
+    pub const fn production(mut self, production_environment: bool) -> Self {
+        self.production_environment = production_environment;
+        self
+    }
+}
+
+/// A status of a [`Deployment`] to create.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DeploymentStatus {
+    /// The state of the deployment.
+    pub state: DeploymentState,
+    /// The URL of the deployed environment, like the deployed site.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment_url: Option<String>,
+    /// The URL to the logs of the deployment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_url: Option<String>,
+    /// A short description of the status.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl DeploymentStatus {
+    /// Creates a [`DeploymentStatus`].
+    pub const fn new(state: DeploymentState) -> Self {
+        Self {
+            state,
+            environment_url: None,
+            log_url: None,
+            description: None,
+        }
+    }
+
+    /// Sets the URL of the deployed environment.
+    pub fn environment_url<S>(mut self, environment_url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.environment_url = Some(environment_url.into());
+        self
+    }
+
+    /// Sets the URL to the logs of the deployment.
+    pub fn log_url<S>(mut self, log_url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.log_url = Some(log_url.into());
+        self
+    }
+
+    /// Sets the description.
+    pub fn description<S>(mut self, description: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// The state of a [`Deployment`].
+///
+/// Once a deployment succeeds, GitHub marks the previous deployments to the same environment as inactive.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentState {
+    /// The deployment is queued.
+    Queued,
+    /// The deployment is pending.
+    Pending,
+    /// The deployment is in progress.
+    InProgress,
+    /// The deployment succeeded.
+    Success,
+    /// The deployment failed.
+    Failure,
+    /// The deployment errored.
+    Error,
+    /// The deployment is no longer active.
+    Inactive,
+}
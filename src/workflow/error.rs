@@ -0,0 +1,183 @@
+//! Errors returned by GitHub REST API.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use reqwest::{Response, StatusCode, Url};
+use serde::Deserialize;
+
+use crate::{framework::StateError, workflow::rate_limit::RateLimited};
+
+/// An error response from GitHub REST API, like `{ "message": "Not Found", "documentation_url": "..." }`.
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct GitHubError {
+    /// The status of the response.
+    pub status: StatusCode,
+    /// The requested URL.
+    pub url: Url,
+    /// The message explaining the error, or the raw body if it is not a JSON error object.
+    pub message: String,
+    /// The URL to the documentation of the endpoint, if provided.
+    pub documentation_url: Option<String>,
+    /// The rate limit hit by the response, if any.
+    pub rate_limited: Option<RateLimited>,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+    documentation_url: Option<String>,
+}
+
+impl GitHubError {
+    /// Reads an error from a response with an error status, consuming the body.
+    pub async fn from_response(response: Response) -> Self {
+        let status = response.status();
+        let url = response.url().clone();
+        let headers = response.headers().clone();
+
+        let body = response.text().await.unwrap_or_default();
+        let rate_limited = RateLimited::from_response_body(status, &headers, &body);
+        let (message, documentation_url) = match serde_json::from_str::<ErrorBody>(&body) {
+            Ok(ErrorBody {
+                message,
+                documentation_url,
+            }) => (message, documentation_url),
+            Err(_) => (body.trim().to_owned(), None),
+        };
+
+        Self {
+            status,
+            url,
+            message,
+            documentation_url,
+            rate_limited,
+        }
+    }
+
+    /// Checks if the error is caused by invalid or insufficient credentials, rather than a rate limit.
+    pub fn is_auth(&self) -> bool {
+        self.rate_limited.is_none()
+            && (self.status == StatusCode::UNAUTHORIZED || self.status == StatusCode::FORBIDDEN)
+    }
+
+    /// Checks if the error is transient, so that the request may succeed if retried later.
+    ///
+    /// Rate limits, `408 Request Timeout`, `429 Too Many Requests` and server errors are transient.
+    pub fn is_transient(&self) -> bool {
+        self.rate_limited.is_some()
+            || self.status == StatusCode::REQUEST_TIMEOUT
+            || self.status == StatusCode::TOO_MANY_REQUESTS
+            || self.status.is_server_error()
+    }
+}
+
+impl Display for GitHubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} from {}", self.status, self.url)?;
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        if let Some(rate_limited) = &self.rate_limited {
+            write!(f, " ({rate_limited})")?;
+        } else if self.is_auth() {
+            write!(f, " (check the credentials and their permissions)")?;
+        }
+        if let Some(documentation_url) = &self.documentation_url {
+            write!(f, ", see {documentation_url}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for GitHubError {}
+
+impl From<&GitHubError> for StateError {
+    /// Retries transient errors, and cancels the others, like failed authentication, missing or expired resources, and invalid requests.
+    fn from(err: &GitHubError) -> Self {
+        if err.is_transient() {
+            Self::Retry
+        } else {
+            Self::Cancelled
+        }
+    }
+}
+
+impl From<GitHubError> for StateError {
+    fn from(err: GitHubError) -> Self {
+        Self::from(&err)
+    }
+}
+
+/// Turns a response with an error status into a [`GitHubError`], or passes it through.
+///
+/// # Errors
+///
+/// Returns an error if the status is a client or server error.
+pub async fn error_for_status(response: Response) -> Result<Response, GitHubError> {
+    if response.status().is_client_error() || response.status().is_server_error() {
+        Err(GitHubError::from_response(response).await)
+    } else {
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{Mock, MockServer, ResponseTemplate, matchers::path};
+
+    use super::*;
+
+    async fn error(status: u16, body: &str) -> GitHubError {
+        let server = MockServer::start().await;
+        Mock::given(path("/"))
+            .respond_with(ResponseTemplate::new(status).set_body_string(body))
+            .mount(&server)
+            .await;
+        let response = reqwest::get(server.uri()).await.unwrap();
+        error_for_status(response).await.unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn classifies_statuses() {
+        let err = error(
+            404,
+            r#"{"message":"Not Found","documentation_url":"https://docs.github.com/rest"}"#,
+        )
+        .await;
+        assert_eq!(err.message, "Not Found");
+        assert_eq!(
+            err.documentation_url.as_deref(),
+            Some("https://docs.github.com/rest")
+        );
+        assert_eq!(StateError::from(err), StateError::Cancelled);
+
+        let err = error(401, r#"{"message":"Bad credentials"}"#).await;
+        assert!(err.is_auth());
+        assert_eq!(StateError::from(err), StateError::Cancelled);
+
+        let err = error(
+            403,
+            r#"{"message":"You have exceeded a secondary rate limit. Please wait a few minutes before you try again."}"#,
+        )
+        .await;
+        assert!(!err.is_auth());
+        assert_eq!(StateError::from(err), StateError::Retry);
+
+        for status in [410, 422] {
+            assert_eq!(
+                StateError::from(error(status, "").await),
+                StateError::Cancelled
+            );
+        }
+        for status in [408, 429, 500, 502, 503] {
+            assert_eq!(
+                StateError::from(error(status, "<html>").await),
+                StateError::Retry
+            );
+        }
+    }
+}
@@ -2,16 +2,18 @@
 
 #![cfg(feature = "workflow")]
 
-use serde::Deserialize;
+mod run;
 
 pub mod artifact;
+pub mod auth;
+pub mod cache;
+pub mod client;
+pub mod deployment;
+pub mod error;
+pub mod pagination;
+pub mod policy;
+pub mod rate_limit;
+pub mod selector;
+pub mod status;
 
-/// Represents a GitHub Actions workflow run from GitHub REST API.
-#[derive(Debug, Deserialize, Clone)]
-pub struct WorkflowRun {
-    pub id: u64,
-    pub repository_id: u64,
-    pub head_repository_id: u64,
-    pub head_branch: String,
-    pub head_sha: String,
-}
+pub use run::*;
@@ -0,0 +1,63 @@
+//! Pagination of list endpoints of GitHub REST API.
+
+use reqwest::header::{self, HeaderMap};
+
+/// A page of a paginated list endpoint, wrapping the items in an object like `{ "total_count": 1, "artifacts": [...] }`.
+pub trait Page {
+    /// The type of the items.
+    type Item;
+
+    /// Takes the items out of the page.
+    fn into_items(self) -> Vec<Self::Item>;
+}
+
+/// A page that is a bare array, like those of GitLab REST API.
+impl<T> Page for Vec<T> {
+    type Item = T;
+
+    fn into_items(self) -> Vec<Self::Item> {
+        self
+    }
+}
+
+/// Parses the URL of the next page from a `Link` header, like `<https://api.github.com/...?page=2>; rel="next", <...>; rel="last"`.
+pub fn next_link(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::LINK)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .find_map(|link| {
+            let mut parts = link.split(';').map(str::trim);
+            let url = parts.next()?.strip_prefix('<')?.strip_suffix('>')?;
+            parts
+                .any(|param| param == r#"rel="next""# || param == "rel=next")
+                .then(|| url.to_owned())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn parses_next_link() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::LINK,
+            HeaderValue::from_static(
+                r#"<https://api.github.com/repositories/1/actions/runs/2/artifacts?per_page=100&page=1>; rel="prev", <https://api.github.com/repositories/1/actions/runs/2/artifacts?per_page=100&page=3>; rel="next", <https://api.github.com/repositories/1/actions/runs/2/artifacts?per_page=100&page=5>; rel="last""#,
+            ),
+        );
+
+        assert_eq!(
+            next_link(&headers).as_deref(),
+            Some(
+                "https://api.github.com/repositories/1/actions/runs/2/artifacts?per_page=100&page=3"
+            )
+        );
+        assert_eq!(next_link(&HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,226 @@
+//! Policies deciding which workflow runs may be deployed.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use crate::workflow::{RunConclusion, WorkflowRun};
+
+/// A policy deciding whether the artifacts of a [`WorkflowRun`] may be deployed.
+///
+/// By default, only successful runs not built from forks are allowed, from any repository, branch and event.
+///
+/// # Examples
+///
+/// ```rust
+/// # use api_framework::workflow::{WorkflowRun, RunConclusion, RunStatus, policy::DeploymentPolicy};
+/// let policy = DeploymentPolicy::new().branch("main").event("push");
+///
+/// let run = WorkflowRun::new(1, 42, "main", "acb5820")
+///     .with_event("push")
+///     .with_status(RunStatus::Completed, Some(RunConclusion::Success));
+/// assert!(policy.evaluate(&run).is_ok());
+///
+/// let fork = run.clone().with_head_repository_id(43);
+/// assert!(policy.evaluate(&fork).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct DeploymentPolicy {
+    repository_ids: Vec<u64>,
+    branches: Vec<String>,
+    events: Vec<String>,
+    conclusion: Option<RunConclusion>,
+    head_sha: Option<String>,
+    allow_forks: bool,
+}
+
+impl DeploymentPolicy {
+    /// Creates a [`DeploymentPolicy`] allowing successful runs not built from forks.
+    pub const fn new() -> Self {
+        Self {
+            repository_ids: Vec::new(),
+            branches: Vec::new(),
+            events: Vec::new(),
+            conclusion: Some(RunConclusion::Success),
+            head_sha: None,
+            allow_forks: false,
+        }
+    }
+
+    /// Allows runs of the repository. Once called, runs of other repositories are denied.
+    pub fn repository_id(mut self, repository_id: u64) -> Self {
+        self.repository_ids.push(repository_id);
+        self
+    }
+
+    /// Allows runs of the branch. Once called, runs of other branches are denied.
+    pub fn branch<S>(mut self, branch: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.branches.push(branch.into());
+        self
+    }
+
+    /// Allows runs triggered by the event, like `push`. Once called, runs triggered by other events are denied.
+    pub fn event<S>(mut self, event: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.events.push(event.into());
+        self
+    }
+
+    /// Requires runs to have the conclusion, or allows any conclusion with [`None`]. Defaults to [`RunConclusion::Success`].
+    pub fn conclusion(mut self, conclusion: Option<RunConclusion>) -> Self {
+        self.conclusion = conclusion;
+        self
+    }
+
+    /// Requires runs of the commit.
+    pub fn head_sha<S>(mut self, head_sha: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.head_sha = Some(head_sha.into());
+        self
+    }
+
+    /// Allows runs built from forks, whose head repository differs from the repository. Denied by default.
+    pub const fn allow_forks(mut self, allow_forks: bool) -> Self {
+        self.allow_forks = allow_forks;
+        self
+    }
+
+    /// Checks if the policy requires fields missing from a workflow run embedded in an artifact, which must be fetched in full before evaluating.
+    pub fn needs_full_run(&self, run: &WorkflowRun) -> bool {
+        (!self.events.is_empty() && run.event.is_none())
+            || (self.conclusion.is_some() && run.conclusion.is_none())
+    }
+
+    /// Evaluates the policy against a workflow run.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first violated rule.
+    pub fn evaluate(&self, run: &WorkflowRun) -> Result<(), PolicyViolation> {
+        if !self.allow_forks && run.head_repository_id != run.repository_id {
+            return Err(PolicyViolation::Fork {
+                repository_id: run.repository_id,
+                head_repository_id: run.head_repository_id,
+            });
+        }
+        if !self.repository_ids.is_empty() && !self.repository_ids.contains(&run.repository_id) {
+            return Err(PolicyViolation::Repository(run.repository_id));
+        }
+        if !self.branches.is_empty()
+            && !run
+                .head_branch
+                .as_ref()
+                .is_some_and(|branch| self.branches.contains(branch))
+        {
+            return Err(PolicyViolation::Branch(run.head_branch.clone()));
+        }
+        if !self.events.is_empty()
+            && !run
+                .event
+                .as_ref()
+                .is_some_and(|event| self.events.contains(event))
+        {
+            return Err(PolicyViolation::Event(run.event.clone()));
+        }
+        if let Some(conclusion) = &self.conclusion
+            && run.conclusion.as_ref() != Some(conclusion)
+        {
+            return Err(PolicyViolation::Conclusion {
+                expected: conclusion.clone(),
+                actual: run.conclusion.clone(),
+            });
+        }
+        if let Some(head_sha) = &self.head_sha
+            && *head_sha != run.head_sha
+        {
+            return Err(PolicyViolation::HeadSha {
+                expected: head_sha.clone(),
+                actual: run.head_sha.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for DeploymentPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A rule of a [`DeploymentPolicy`] violated by a workflow run.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    /// The artifact has no workflow run to evaluate.
+    MissingWorkflowRun,
+    /// The run is built from a fork.
+    Fork {
+        /// The ID of the repository.
+        repository_id: u64,
+        /// The ID of the fork.
+        head_repository_id: u64,
+    },
+    /// The repository of the run is not allowed.
+    Repository(u64),
+    /// The branch of the run is not allowed, or unknown.
+    Branch(Option<String>),
+    /// The event that triggered the run is not allowed, or unknown.
+    Event(Option<String>),
+    /// The run does not have the required conclusion.
+    Conclusion {
+        /// The required conclusion.
+        expected: RunConclusion,
+        /// The conclusion of the run, if completed.
+        actual: Option<RunConclusion>,
+    },
+    /// The run is not of the required commit.
+    HeadSha {
+        /// The required commit.
+        expected: String,
+        /// The commit of the run.
+        actual: String,
+    },
+}
+
+impl Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingWorkflowRun => write!(f, "no workflow run to evaluate"),
+            Self::Fork {
+                repository_id,
+                head_repository_id,
+            } => write!(
+                f,
+                "built from fork {head_repository_id} of repository {repository_id}"
+            ),
+            Self::Repository(repository_id) => {
+                write!(f, "repository {repository_id} is not allowed")
+            }
+            Self::Branch(Some(branch)) => write!(f, "branch {branch} is not allowed"),
+            Self::Branch(None) => write!(f, "unknown branch is not allowed"),
+            Self::Event(Some(event)) => write!(f, "event {event} is not allowed"),
+            Self::Event(None) => write!(f, "unknown event is not allowed"),
+            Self::Conclusion { expected, actual } => write!(
+                f,
+                "expected conclusion {expected:?}, got {}",
+                actual
+                    .as_ref()
+                    .map_or_else(|| String::from("none"), |actual| format!("{actual:?}"))
+            ),
+            Self::HeadSha { expected, actual } => {
+                write!(f, "expected commit {expected}, got {actual}")
+            }
+        }
+    }
+}
+
+impl Error for PolicyViolation {}
@@ -0,0 +1,235 @@
+//! Rate limits of GitHub REST API.
+
+use std::{
+    fmt::{self, Display},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use reqwest::{
+    StatusCode,
+    header::{self, HeaderMap},
+};
+
+/// How long to wait after hitting a secondary rate limit without a `Retry-After` header, as recommended by GitHub.
+const SECONDARY_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// The state of the primary rate limit, as reported by GitHub REST API in response headers.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The maximum number of requests per hour, from `X-RateLimit-Limit`.
+    pub limit: u32,
+    /// The number of requests remaining in the current window, from `X-RateLimit-Remaining`.
+    pub remaining: u32,
+    /// The time when the current window resets, from `X-RateLimit-Reset`.
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimit {
+    /// Parses the rate limit from response headers. Returns [`None`] if any header is missing or invalid.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        fn parse<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+            headers.get(name)?.to_str().ok()?.parse().ok()
+        }
+
+        Some(Self {
+            limit: parse(headers, "x-ratelimit-limit")?,
+            remaining: parse(headers, "x-ratelimit-remaining")?,
+            reset: DateTime::from_timestamp(parse(headers, "x-ratelimit-reset")?, 0)?,
+        })
+    }
+
+    /// Checks if no requests remain until the window resets.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == 0 && self.reset > Utc::now()
+    }
+}
+
+/// A rate limit hit by a response, with the time when requests may be sent again.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimited {
+    /// The primary rate limit is exhausted until the window resets.
+    Primary(RateLimit),
+    /// A secondary rate limit is hit, like for too many concurrent requests.
+    Secondary {
+        /// The time when requests may be sent again, from `Retry-After` if present.
+        until: DateTime<Utc>,
+    },
+}
+
+impl RateLimited {
+    /// Detects a rate limit from the status and the headers of a response. Returns [`None`] if the response is not rate limited.
+    ///
+    /// Only `403 Forbidden` and `429 Too Many Requests` are considered. A `Retry-After` header marks a secondary rate limit, while no remaining requests mark the primary one. Other `429 Too Many Requests` responses are considered secondary rate limits.
+    pub fn from_response(status: StatusCode, headers: &HeaderMap) -> Option<Self> {
+        if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+            return None;
+        }
+
+        if let Some(until) = retry_after(headers) {
+            return Some(Self::Secondary { until });
+        }
+        match RateLimit::from_headers(headers) {
+            Some(rate_limit) if rate_limit.remaining == 0 => Some(Self::Primary(rate_limit)),
+            _ if status == StatusCode::TOO_MANY_REQUESTS => Some(Self::Secondary {
+                until: Utc::now() + SECONDARY_RATE_LIMIT_WAIT,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Detects a rate limit like [`Self::from_response`], also considering the body of the response.
+    ///
+    /// GitHub may respond to a secondary rate limit with a `403 Forbidden` that has neither a `Retry-After` header nor an exhausted primary rate limit, but mentions the secondary rate limit in its message. Requests may be sent again after a minute then, as recommended by GitHub.
+    pub fn from_response_body(status: StatusCode, headers: &HeaderMap, body: &str) -> Option<Self> {
+        Self::from_response(status, headers).or_else(|| {
+            (status == StatusCode::FORBIDDEN
+                && body.to_ascii_lowercase().contains("secondary rate limit"))
+            .then(|| Self::Secondary {
+                until: Utc::now() + SECONDARY_RATE_LIMIT_WAIT,
+            })
+        })
+    }
+
+    /// Gets the time when requests may be sent again.
+    pub fn until(&self) -> DateTime<Utc> {
+        match self {
+            Self::Primary(rate_limit) => rate_limit.reset,
+            Self::Secondary { until } => *until,
+        }
+    }
+}
+
+impl Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Primary(rate_limit) => write!(
+                f,
+                "primary rate limit of {} requests exhausted until {}",
+                rate_limit.limit, rate_limit.reset
+            ),
+            Self::Secondary { until } => write!(f, "secondary rate limit hit until {until}"),
+        }
+    }
+}
+
+/// Parses a `Retry-After` header, either in seconds or as an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<DateTime<Utc>> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim();
+    match value.parse::<u64>() {
+        Ok(seconds) => Some(Utc::now() + Duration::from_secs(seconds)),
+        Err(_) => DateTime::parse_from_rfc2822(value)
+            .ok()
+            .map(|date| date.to_utc()),
+    }
+}
+
+/// A snapshot of the rate limits observed by a [`GitHubClient`](crate::workflow::client::GitHubClient), for monitoring.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// The primary rate limit reported by the last response.
+    pub last: Option<RateLimit>,
+    /// The time until which requests are paused after hitting a rate limit, if it has not passed yet.
+    pub paused_until: Option<DateTime<Utc>>,
+    /// The number of responses that hit a rate limit.
+    pub hits: u64,
+}
+
+/// Tracks the rate limits observed by a client.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimitTracker(Mutex<RateLimitStatus>);
+
+impl RateLimitTracker {
+    /// Records the primary rate limit from the headers of a response.
+    pub(crate) fn observe(&self, headers: &HeaderMap) {
+        if let Some(rate_limit) = RateLimit::from_headers(headers) {
+            self.0.lock().last = Some(rate_limit);
+        }
+    }
+
+    /// Records a hit rate limit. A secondary rate limit pauses all requests until it resets.
+    pub(crate) fn hit(&self, rate_limited: &RateLimited) {
+        let mut status = self.0.lock();
+        status.hits += 1;
+        if let RateLimited::Secondary { until } = rate_limited {
+            status.paused_until = status.paused_until.max(Some(*until));
+        }
+    }
+
+    /// Gets the time until which requests are paused, if it has not passed yet.
+    pub(crate) fn paused_until(&self) -> Option<DateTime<Utc>> {
+        self.status().paused_until
+    }
+
+    /// Takes a snapshot of the rate limits.
+    pub(crate) fn status(&self) -> RateLimitStatus {
+        let mut status = *self.0.lock();
+        status.paused_until = status.paused_until.filter(|until| *until > Utc::now());
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn detects_rate_limits() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit", HeaderValue::from(5000));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from(0));
+        headers.insert("x-ratelimit-reset", HeaderValue::from(1_700_000_000));
+        assert!(matches!(
+            RateLimited::from_response(StatusCode::FORBIDDEN, &headers),
+            Some(RateLimited::Primary(RateLimit { limit: 5000, .. }))
+        ));
+        assert_eq!(RateLimited::from_response(StatusCode::OK, &headers), None);
+
+        headers.insert(header::RETRY_AFTER, HeaderValue::from(30));
+        assert!(matches!(
+            RateLimited::from_response(StatusCode::FORBIDDEN, &headers),
+            Some(RateLimited::Secondary { .. })
+        ));
+
+        // A plain permission error
+        assert_eq!(
+            RateLimited::from_response(StatusCode::FORBIDDEN, &HeaderMap::new()),
+            None
+        );
+        assert!(
+            RateLimited::from_response(StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new()).is_some()
+        );
+
+        // A secondary rate limit told apart from a permission error by its message only
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit", HeaderValue::from(5000));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from(4999));
+        headers.insert("x-ratelimit-reset", HeaderValue::from(1_700_000_000));
+        assert_eq!(
+            RateLimited::from_response(StatusCode::FORBIDDEN, &headers),
+            None
+        );
+        assert!(matches!(
+            RateLimited::from_response_body(
+                StatusCode::FORBIDDEN,
+                &headers,
+                r#"{"message":"You have exceeded a secondary rate limit. Please wait a few minutes before you try again.","documentation_url":"https://docs.github.com/rest/overview/rate-limits-for-the-rest-api#about-secondary-rate-limits"}"#
+            ),
+            Some(RateLimited::Secondary { .. })
+        ));
+        assert_eq!(
+            RateLimited::from_response_body(
+                StatusCode::FORBIDDEN,
+                &headers,
+                r#"{"message":"Resource not accessible by integration"}"#
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,422 @@
+use std::fmt::{self, Display};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::workflow::pagination::Page;
+
+/// Represents workflow runs from GitHub REST API.
+///
+/// Serialized in the same format as GitHub REST API. Unknown fields are ignored when deserializing.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct WorkflowRuns {
+    /// The total count of workflow runs, across all pages.
+    #[serde(default)]
+    pub total_count: u64,
+    /// The workflow runs.
+    pub workflow_runs: Vec<WorkflowRun>,
+}
+
+impl Page for WorkflowRuns {
+    type Item = WorkflowRun;
+
+    fn into_items(self) -> Vec<Self::Item> {
+        self.workflow_runs
+    }
+}
+
+/// Represents a GitHub Actions workflow run from GitHub REST API.
+///
+/// Workflow runs embedded in artifacts only contain the identifiers, the head branch and the head SHA, so the other fields are optional.
+///
+/// Serialized in the format of workflow runs embedded in artifacts, so that it can be persisted or forwarded. Both that format and full workflow runs, which nest the repositories instead of their IDs, are deserialized. Unknown fields are ignored when deserializing.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(try_from = "RawWorkflowRun")]
+pub struct WorkflowRun {
+    /// The unique identifier of the workflow run.
+    pub id: u64,
+    /// The ID of the repository the workflow run belongs to.
+    pub repository_id: u64,
+    /// The ID of the repository the head commit belongs to. Differs from `repository_id` for forks.
+    pub head_repository_id: u64,
+    /// The branch of the head commit, missing for runs not triggered on a branch.
+    pub head_branch: Option<String>,
+    /// The SHA of the head commit.
+    pub head_sha: String,
+    /// The name of the workflow run.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The ID of the workflow.
+    #[serde(default)]
+    pub workflow_id: Option<u64>,
+    /// The number of the workflow run, incrementing per workflow.
+    #[serde(default)]
+    pub run_number: Option<u64>,
+    /// The attempt of the workflow run, starting from 1 and incrementing per re-run.
+    #[serde(default)]
+    pub run_attempt: Option<u64>,
+    /// The event that triggered the workflow run, like `push` or `pull_request`.
+    #[serde(default)]
+    pub event: Option<String>,
+    /// The status of the workflow run.
+    #[serde(default)]
+    pub status: Option<RunStatus>,
+    /// The conclusion of the workflow run, available once completed.
+    #[serde(default)]
+    pub conclusion: Option<RunConclusion>,
+    /// The user who triggered the workflow run.
+    #[serde(default)]
+    pub actor: Option<Actor>,
+    /// The time when the workflow run was created.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// The time when the workflow run was last updated.
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// The time when the latest attempt of the workflow run started.
+    #[serde(default)]
+    pub run_started_at: Option<DateTime<Utc>>,
+    /// The URL to the workflow run on GitHub.
+    #[serde(default)]
+    pub html_url: Option<String>,
+}
+
+/// A workflow run as sent by GitHub REST API, either embedded in an artifact or in full.
+#[derive(Deserialize)]
+struct RawWorkflowRun {
+    id: u64,
+    #[serde(default)]
+    repository_id: Option<u64>,
+    #[serde(default)]
+    repository: Option<RawRepository>,
+    #[serde(default)]
+    head_repository_id: Option<u64>,
+    #[serde(default)]
+    head_repository: Option<RawRepository>,
+    #[serde(default)]
+    head_branch: Option<String>,
+    head_sha: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    workflow_id: Option<u64>,
+    #[serde(default)]
+    run_number: Option<u64>,
+    #[serde(default)]
+    run_attempt: Option<u64>,
+    #[serde(default)]
+    event: Option<String>,
+    #[serde(default)]
+    status: Option<RunStatus>,
+    #[serde(default)]
+    conclusion: Option<RunConclusion>,
+    #[serde(default)]
+    actor: Option<Actor>,
+    #[serde(default)]
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    run_started_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    html_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawRepository {
+    id: u64,
+}
+
+impl TryFrom<RawWorkflowRun> for WorkflowRun {
+    type Error = String;
+
+    fn try_from(raw: RawWorkflowRun) -> Result<Self, Self::Error> {
+        let repository_id = raw
+            .repository_id
+            .or(raw.repository.map(|repository| repository.id))
+            .ok_or("missing field `repository_id` or `repository`")?;
+        let head_repository_id = raw
+            .head_repository_id
+            .or(raw.head_repository.map(|repository| repository.id))
+            .unwrap_or(repository_id);
+        Ok(Self {
+            id: raw.id,
+            repository_id,
+            head_repository_id,
+            head_branch: raw.head_branch,
+            head_sha: raw.head_sha,
+            name: raw.name,
+            workflow_id: raw.workflow_id,
+            run_number: raw.run_number,
+            run_attempt: raw.run_attempt,
+            event: raw.event,
+            status: raw.status,
+            conclusion: raw.conclusion,
+            actor: raw.actor,
+            created_at: raw.created_at,
+            updated_at: raw.updated_at,
+            run_started_at: raw.run_started_at,
+            html_url: raw.html_url,
+        })
+    }
+}
+
+impl WorkflowRuns {
+    /// Creates [`WorkflowRuns`] of a single page.
+    pub fn new(workflow_runs: Vec<WorkflowRun>) -> Self {
+        Self {
+            total_count: workflow_runs.len() as u64,
+            workflow_runs,
+        }
+    }
+}
+
+impl WorkflowRun {
+    /// Creates a [`WorkflowRun`] in a repository, not from a fork. The other fields are empty.
+    pub fn new<B, S>(id: u64, repository_id: u64, head_branch: B, head_sha: S) -> Self
+    where
+        B: Into<String>,
+        S: Into<String>,
+    {
+        Self {
+            id,
+            repository_id,
+            head_repository_id: repository_id,
+            head_branch: Some(head_branch.into()),
+            head_sha: head_sha.into(),
+            name: None,
+            workflow_id: None,
+            run_number: None,
+            run_attempt: None,
+            event: None,
+            status: None,
+            conclusion: None,
+            actor: None,
+            created_at: None,
+            updated_at: None,
+            run_started_at: None,
+            html_url: None,
+        }
+    }
+
+    /// Sets the name and the number of the workflow run.
+    pub fn with_name<S>(mut self, name: S, run_number: u64) -> Self
+    where
+        S: Into<String>,
+    {
+        self.name = Some(name.into());
+        self.run_number = Some(run_number);
+        self
+    }
+
+    /// Sets the event that triggered the workflow run.
+    pub fn with_event<S>(mut self, event: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// Sets the status of the workflow run, and the conclusion if completed.
+    pub fn with_status(mut self, status: RunStatus, conclusion: Option<RunConclusion>) -> Self {
+        self.status = Some(status);
+        self.conclusion = conclusion;
+        self
+    }
+
+    /// Sets the ID of the repository the head commit belongs to, as for a fork.
+    pub const fn with_head_repository_id(mut self, head_repository_id: u64) -> Self {
+        self.head_repository_id = head_repository_id;
+        self
+    }
+
+    /// Checks if the workflow run is completed.
+    pub fn is_completed(&self) -> bool {
+        self.status == Some(RunStatus::Completed)
+    }
+
+    /// Checks if the workflow run is completed successfully.
+    pub fn is_successful(&self) -> bool {
+        self.is_completed() && self.conclusion == Some(RunConclusion::Success)
+    }
+}
+
+impl Display for WorkflowRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.name, self.run_number) {
+            (Some(name), Some(run_number)) => write!(f, "{name} #{run_number} ({})", self.id),
+            _ => write!(f, "{}", self.id),
+        }
+    }
+}
+
+/// The status of a workflow run.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// The workflow run is requested.
+    Requested,
+    /// The workflow run is queued.
+    Queued,
+    /// The workflow run is pending.
+    Pending,
+    /// The workflow run is waiting for approval or a deployment protection rule.
+    Waiting,
+    /// The workflow run is in progress.
+    InProgress,
+    /// The workflow run is completed.
+    Completed,
+    /// A status unknown to this crate, kept as sent by GitHub.
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl RunStatus {
+    /// Gets the value used by GitHub REST API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Requested => "requested",
+            Self::Queued => "queued",
+            Self::Pending => "pending",
+            Self::Waiting => "waiting",
+            Self::InProgress => "in_progress",
+            Self::Completed => "completed",
+            Self::Unknown(value) => value,
+        }
+    }
+}
+
+/// The conclusion of a completed workflow run.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RunConclusion {
+    /// The workflow run succeeded.
+    Success,
+    /// The workflow run failed.
+    Failure,
+    /// The workflow run concluded neutrally.
+    Neutral,
+    /// The workflow run was cancelled.
+    Cancelled,
+    /// The workflow run was skipped.
+    Skipped,
+    /// The workflow run timed out.
+    TimedOut,
+    /// The workflow run requires an action.
+    ActionRequired,
+    /// The workflow run became stale.
+    Stale,
+    /// The workflow run failed to start.
+    StartupFailure,
+    /// A conclusion unknown to this crate, kept as sent by GitHub.
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl RunConclusion {
+    /// Gets the value used by GitHub REST API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Success => "success",
+            Self::Failure => "failure",
+            Self::Neutral => "neutral",
+            Self::Cancelled => "cancelled",
+            Self::Skipped => "skipped",
+            Self::TimedOut => "timed_out",
+            Self::ActionRequired => "action_required",
+            Self::Stale => "stale",
+            Self::StartupFailure => "startup_failure",
+            Self::Unknown(value) => value,
+        }
+    }
+}
+
+/// Represents a GitHub user from GitHub REST API.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Actor {
+    /// The unique identifier of the user.
+    pub id: u64,
+    /// The username of the user.
+    pub login: String,
+}
+
+impl Actor {
+    /// Creates an [`Actor`].
+    pub fn new<S>(id: u64, login: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            id,
+            login: login.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_full_runs() {
+        let run: WorkflowRun =
+            serde_json::from_str(include_str!("../../tests/fixtures/workflow_run.json")).unwrap();
+        assert_eq!(run.id, 30_433_642);
+        assert_eq!(run.repository_id, 1_296_269);
+        assert_eq!(run.head_repository_id, 217_723_378);
+        assert_eq!(run.head_branch.as_deref(), Some("master"));
+        assert_eq!(run.head_sha, "acb5820ced9479c074f688cc328bf03f341a511d");
+        assert_eq!(run.to_string(), "Build #562 (30433642)");
+        assert_eq!(run.run_attempt, Some(1));
+        assert_eq!(run.status, Some(RunStatus::Queued));
+        assert_eq!(run.conclusion, None);
+        assert_eq!(run.actor, Some(Actor::new(1, "octocat")));
+
+        // Serialized in the embedded format, which is decoded again
+        let json = serde_json::to_string(&run).unwrap();
+        assert_eq!(serde_json::from_str::<WorkflowRun>(&json).unwrap(), run);
+    }
+
+    #[test]
+    fn decodes_runs_embedded_in_artifacts() {
+        let run: WorkflowRun = serde_json::from_str(
+            r#"{
+                "id": 2332938,
+                "repository_id": 1296269,
+                "head_repository_id": 1296269,
+                "head_branch": null,
+                "head_sha": "328faa0536e6fef19753d9d91dc96a9931694ce3"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(run.repository_id, 1_296_269);
+        assert_eq!(run.head_repository_id, 1_296_269);
+        assert_eq!(run.head_branch, None);
+
+        let missing = serde_json::from_str::<WorkflowRun>(r#"{ "id": 1, "head_sha": "328faa0" }"#);
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn keeps_unknown_statuses() {
+        let status: RunStatus = serde_json::from_str(r#""in_progress""#).unwrap();
+        assert_eq!(status, RunStatus::InProgress);
+
+        let status: RunStatus = serde_json::from_str(r#""paused""#).unwrap();
+        assert_eq!(status, RunStatus::Unknown(String::from("paused")));
+        assert_eq!(status.as_str(), "paused");
+        assert_eq!(serde_json::to_string(&status).unwrap(), r#""paused""#);
+
+        let conclusion: RunConclusion = serde_json::from_str(r#""superseded""#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&conclusion).unwrap(),
+            r#""superseded""#
+        );
+    }
+}
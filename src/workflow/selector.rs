@@ -0,0 +1,147 @@
+//! Selection of artifacts by name.
+
+use std::fmt::{self, Display};
+
+use regex::Regex;
+
+/// Selects artifacts by their names.
+///
+/// # Examples
+///
+/// ```rust
+/// # use api_framework::workflow::selector::ArtifactSelector;
+/// let selector = ArtifactSelector::glob("site-linux-*").unwrap();
+/// assert!(selector.matches("site-linux-x64"));
+/// assert!(!selector.matches("site-docs"));
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub enum ArtifactSelector {
+    /// Matches the exact name. The name is also sent to GitHub to filter the artifacts on the server.
+    Name(String),
+    /// Matches a glob pattern, where `*` matches any sequence of characters, `?` matches any single character, and `[...]` or `[!...]` matches a character class.
+    Glob {
+        /// The original glob pattern.
+        pattern: String,
+        /// The glob pattern translated to a regular expression.
+        regex: Regex,
+    },
+    /// Matches a regular expression, which is unanchored unless `^` and `$` are given.
+    Regex(Regex),
+}
+
+impl ArtifactSelector {
+    /// Creates an [`ArtifactSelector`] matching the exact name.
+    pub fn name<S>(name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::Name(name.into())
+    }
+
+    /// Creates an [`ArtifactSelector`] matching a glob pattern.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern contains an invalid or unterminated character class.
+    pub fn glob(pattern: &str) -> Result<Self, regex::Error> {
+        let mut translated = String::from("^");
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => translated.push_str(".*"),
+                '?' => translated.push('.'),
+                '[' => {
+                    let class = chars.as_str();
+                    let Some(end) = class.find(']') else {
+                        return Err(regex::Error::Syntax(format!(
+                            "unterminated character class in glob {pattern:?}"
+                        )));
+                    };
+                    let (class, negated) = match class[..end].strip_prefix('!') {
+                        Some(class) => (class, true),
+                        None => (&class[..end], false),
+                    };
+                    translated.push('[');
+                    if negated {
+                        translated.push('^');
+                    }
+                    for c in class.chars() {
+                        if c == '\\' || c == '[' {
+                            translated.push('\\');
+                        }
+                        translated.push(c);
+                    }
+                    translated.push(']');
+                    chars = chars.as_str()[end + 1..].chars();
+                }
+                c => translated.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        translated.push('$');
+
+        Ok(Self::Glob {
+            pattern: pattern.to_owned(),
+            regex: Regex::new(&translated)?,
+        })
+    }
+
+    /// Creates an [`ArtifactSelector`] matching a regular expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the regular expression is invalid.
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        Regex::new(pattern).map(Self::Regex)
+    }
+
+    /// Checks if an artifact name is selected.
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::Name(expected) => name == expected,
+            Self::Glob { regex, .. } | Self::Regex(regex) => regex.is_match(name),
+        }
+    }
+
+    /// Gets the name to filter artifacts on the server, which is only available for exact names.
+    pub fn exact_name(&self) -> Option<&str> {
+        match self {
+            Self::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ArtifactSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "name {name:?}"),
+            Self::Glob { pattern, .. } => write!(f, "glob {pattern:?}"),
+            Self::Regex(regex) => write!(f, "regex {:?}", regex.as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_globs() {
+        let selector = ArtifactSelector::glob("site-*-x[0-9][!a-z]").unwrap();
+        assert!(selector.matches("site-linux-x64"));
+        assert!(selector.matches("site-windows-x86"));
+        assert!(!selector.matches("site-linux-arm64"));
+        assert!(!selector.matches("site-linux-x6a"));
+
+        let selector = ArtifactSelector::glob("coverage.???").unwrap();
+        assert!(selector.matches("coverage.xml"));
+        assert!(!selector.matches("coverage_xml"));
+    }
+
+    #[test]
+    fn rejects_unterminated_classes() {
+        assert!(ArtifactSelector::glob("site-[linux").is_err());
+        assert!(ArtifactSelector::glob("site-[!").is_err());
+    }
+}
@@ -0,0 +1,221 @@
+//! Commit statuses and check runs from GitHub REST API, reporting results for commits.
+
+use std::fmt::{self, Display};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::workflow::RunConclusion;
+
+/// The maximum length of the description of a commit status.
+const MAX_DESCRIPTION_LENGTH: usize = 140;
+
+/// A commit status to create, shown next to the commit on GitHub.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CommitStatus {
+    /// The state of the status.
+    pub state: CommitState,
+    /// The label distinguishing the status from those of other systems, like `deploy/production`.
+    pub context: String,
+    /// A short description of the status, truncated to 140 characters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The URL linked from the status, like the deployed site or the logs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_url: Option<String>,
+}
+
+impl CommitStatus {
+    /// Creates a [`CommitStatus`] in a context.
+    pub fn new<S>(state: CommitState, context: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            state,
+            context: context.into(),
+            description: None,
+            target_url: None,
+        }
+    }
+
+    /// Sets the description, truncated to 140 characters.
+    pub fn description<S>(mut self, description: S) -> Self
+    where
+        S: Into<String>,
+    {
+        let description: String = description.into();
+        self.description = Some(
+            match description.char_indices().nth(MAX_DESCRIPTION_LENGTH) {
+                Some((end, _)) => description[..end].to_owned(),
+                None => description,
+            },
+        );
+        self
+    }
+
+    /// Sets the URL linked from the status.
+    pub fn target_url<S>(mut self, target_url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.target_url = Some(target_url.into());
+        self
+    }
+}
+
+/// The state of a [`CommitStatus`].
+#[allow(clippy::exhaustive_enums)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitState {
+    /// The commit is being processed.
+    Pending,
+    /// The commit succeeded.
+    Success,
+    /// The commit failed.
+    Failure,
+    /// Processing the commit errored.
+    Error,
+}
+
+/// Represents a check run from GitHub REST API.
+///
+/// Creating check runs requires authenticating as a GitHub App.
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CheckRun {
+    /// The unique identifier of the check run.
+    pub id: u64,
+    /// The name of the check run.
+    pub name: String,
+    /// The SHA of the commit being checked.
+    pub head_sha: String,
+    /// The status of the check run.
+    pub status: CheckRunStatus,
+    /// The conclusion of the check run, available once completed.
+    #[serde(default)]
+    pub conclusion: Option<RunConclusion>,
+    /// The URL to the check run on GitHub.
+    #[serde(default)]
+    pub html_url: Option<String>,
+}
+
+impl Display for CheckRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}) for {}", self.name, self.id, self.head_sha)
+    }
+}
+
+/// The status of a [`CheckRun`].
+#[non_exhaustive]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckRunStatus {
+    /// The check run is queued.
+    Queued,
+    /// The check run is in progress.
+    InProgress,
+    /// The check run is completed.
+    Completed,
+    /// A status unknown to this crate, kept as sent by GitHub.
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// The conclusion to complete a [`CheckRun`] with.
+///
+/// Unlike [`RunConclusion`], only the conclusions accepted by GitHub when creating or updating a check run are included. Stale check runs are only marked by GitHub.
+#[allow(clippy::exhaustive_enums)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckRunConclusion {
+    /// The check run succeeded.
+    Success,
+    /// The check run failed.
+    Failure,
+    /// The check run concluded neutrally.
+    Neutral,
+    /// The check run was cancelled.
+    Cancelled,
+    /// The check run was skipped.
+    Skipped,
+    /// The check run timed out.
+    TimedOut,
+    /// The check run requires an action, linked by the details URL.
+    ActionRequired,
+}
+
+/// The changes to a [`CheckRun`] when creating or updating it. Unset fields are left unchanged.
+#[derive(Debug, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct CheckRunUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<CheckRunStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conclusion: Option<CheckRunConclusion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    completed_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<CheckRunOutput>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+struct CheckRunOutput {
+    title: String,
+    summary: String,
+}
+
+impl CheckRunUpdate {
+    /// Creates a [`CheckRunUpdate`] changing nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks the check run as queued.
+    pub fn queued(mut self) -> Self {
+        self.status = Some(CheckRunStatus::Queued);
+        self
+    }
+
+    /// Marks the check run as in progress, starting now.
+    pub fn in_progress(mut self) -> Self {
+        self.status = Some(CheckRunStatus::InProgress);
+        self.started_at = Some(Utc::now());
+        self
+    }
+
+    /// Marks the check run as completed now with the conclusion.
+    pub fn completed(mut self, conclusion: CheckRunConclusion) -> Self {
+        self.status = Some(CheckRunStatus::Completed);
+        self.conclusion = Some(conclusion);
+        self.completed_at = Some(Utc::now());
+        self
+    }
+
+    /// Sets the URL to the details of the check run, like the deployed site or the logs.
+    pub fn details_url<S>(mut self, details_url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.details_url = Some(details_url.into());
+        self
+    }
+
+    /// Sets the title and the Markdown summary shown on the check run.
+    pub fn output<T, S>(mut self, title: T, summary: S) -> Self
+    where
+        T: Into<String>,
+        S: Into<String>,
+    {
+        self.output = Some(CheckRunOutput {
+            title: title.into(),
+            summary: summary.into(),
+        });
+        self
+    }
+}